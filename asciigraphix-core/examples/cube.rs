@@ -1,10 +1,11 @@
 use asciigraphix_core::{
-    shapes::{Point, Shape}, Display
+    color::{ColorDepth, Dither}, record::ArgsRecorder, shapes::{Point, Shape}, Display
 };
 
 fn main() {
     const DISPLAY_X: usize = 100;
     const DISPLAY_Y: usize = 50;
+    const RECORD_FRAMES: usize = 300;
     let mut display = Display::new(
         DISPLAY_X,
         DISPLAY_Y,
@@ -12,14 +13,22 @@ fn main() {
         Point(0.0, 0.0, 1.0),
         20.0,
     );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    let mut recording = ArgsRecorder::from_args(DISPLAY_X, DISPLAY_Y, RECORD_FRAMES).expect("failed to start recording");
     let mut cube = Shape::generate_cube(Point(0.0, 0.0, 0.0), 14.0);
     loop {
         cube.rotate(
-            &cube.center.clone().unwrap_or(Point(0.0, 0.0, 0.0)),
+            &cube.center.unwrap_or(Point(0.0, 0.0, 0.0)),
             (0.05, 0.05, 0.0),
         );
         display.render_print(&cube);
 
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
         std::thread::sleep(std::time::Duration::from_millis(33));
     }
 }
@@ -0,0 +1,53 @@
+use asciigraphix_core::{
+    color::{ColorDepth, Dither},
+    cue::DepthCue,
+    record::ArgsRecorder,
+    scene::Transform,
+    shapes::{Point, Shape},
+    Display, HiddenLines,
+};
+
+// The spinning donut, pass --wireframe to see through it.
+fn main() {
+    const DISPLAY_X: usize = 100;
+    const DISPLAY_Y: usize = 50;
+    const RECORD_FRAMES: usize = 300;
+    let mut display = Display::new(
+        DISPLAY_X,
+        DISPLAY_Y,
+        Point(0.0, -35.0, 0.0),
+        Point(0.0, 1.0, 0.0),
+        30.0,
+    );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    // the glyph ramp and fog over just the donut's depth
+    display.set_depth_cue(DepthCue {
+        near: 18.0,
+        far: 52.0,
+        max_fog: 0.5,
+        ..DepthCue::default()
+    });
+    if !std::env::args().any(|arg| arg == "--wireframe") {
+        display.set_solid(true);
+        display.set_hidden_lines(HiddenLines::Hide);
+    }
+    let mut recording = ArgsRecorder::from_args(DISPLAY_X, DISPLAY_Y, RECORD_FRAMES).expect("failed to start recording");
+    let torus = Shape::generate_torus(Point::zero(), 12.0, 5.0, 36, 16);
+    let mut donut = Shape::generate_torus(Point::zero(), 12.0, 5.0, 36, 16);
+    for frame in 0.. {
+        let t = frame as f64;
+        let turn = Transform::rotation((0.04 * t, 0.0, 0.02 * t));
+        for (v, vertex) in donut.vertices.iter_mut().zip(&torus.vertices) {
+            *v = turn.apply(vertex);
+        }
+        display.render_print(&donut);
+
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
@@ -0,0 +1,61 @@
+use asciigraphix_core::{
+    color::{ColorDepth, Dither},
+    record::ArgsRecorder,
+    scene::{Instance, Model, Transform},
+    shapes::{Point, Shape},
+    Display, RGB,
+};
+
+// A cubic crystal lattice, one small cube drawn at every site.
+fn main() {
+    const RECORD_FRAMES: usize = 300;
+    // sites along each side and the distance between them
+    const SIDE: usize = 8;
+    const SPACING: f64 = 6.0;
+    let (display_x, display_y) = (160, 60);
+    let mut display = Display::new(
+        display_x,
+        display_y,
+        Point(0.0, -80.0, 0.0),
+        Point(0.0, 1.0, 0.0),
+        60.0,
+    );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    let cube = Model::new(Shape::generate_cube(Point::zero(), 1.5));
+    let half = (SIDE - 1) as f64 / 2.0;
+    let sites: Vec<(Point, RGB)> = (0..SIDE * SIDE * SIDE)
+        .map(|i| {
+            let (x, y, z) = (i % SIDE, i / SIDE % SIDE, i / (SIDE * SIDE));
+            let at = Point(x as f64 - half, y as f64 - half, z as f64 - half) * SPACING;
+            // alternating atoms, like table salt
+            let color = if (x + y + z) % 2 == 0 { RGB(80, 200, 255) } else { RGB(255, 180, 60) };
+            (at, color)
+        })
+        .collect();
+    let mut instances = vec![Instance::default(); sites.len()];
+    let mut recording = ArgsRecorder::from_args(display_x, display_y, RECORD_FRAMES).expect("failed to start recording");
+
+    for frame in 0.. {
+        let t = frame as f64 * 0.01;
+        let lattice = Transform::rotation((0.4, t, 0.2 * t.sin()));
+        for (instance, (at, color)) in instances.iter_mut().zip(&sites) {
+            // every cube spins in place as the whole lattice turns
+            *instance = Instance {
+                transform: Transform::rotation((t, 2.0 * t, 0.0))
+                    .then(&Transform::translation(*at))
+                    .then(&lattice),
+                color: Some(*color),
+            };
+        }
+        display.draw_instances(&cube, &instances);
+        display.print();
+
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
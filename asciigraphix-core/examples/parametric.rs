@@ -0,0 +1,72 @@
+use std::f64::consts::{PI, TAU};
+
+use asciigraphix_core::{
+    color::{ColorDepth, Dither},
+    record::ArgsRecorder,
+    scene::{Material, Scene, Transform},
+    shapes::{Point, Shape},
+    Display, RGB,
+};
+
+// A trefoil knot, a helix and a Möbius strip, all from closures.
+fn main() {
+    const DISPLAY_X: usize = 150;
+    const DISPLAY_Y: usize = 50;
+    const RECORD_FRAMES: usize = 300;
+    let mut display = Display::new(
+        DISPLAY_X,
+        DISPLAY_Y,
+        Point(0.0, -60.0, 0.0),
+        Point(0.0, 1.0, 0.0),
+        30.0,
+    );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    let trefoil = |t: f64| {
+        Point(
+            t.sin() + 2.0 * (2.0 * t).sin(),
+            t.cos() - 2.0 * (2.0 * t).cos(),
+            -(3.0 * t).sin(),
+        ) * 4.0
+    };
+    let helix = |t: f64| Point(4.0 * t.cos(), 4.0 * t.sin(), 1.5 * t - 3.0 * PI);
+    let mobius = |u: f64, v: f64| {
+        let r = 8.0 + v * (u / 2.0).cos();
+        Point(r * u.cos(), r * u.sin(), v * (u / 2.0).sin())
+    };
+
+    let mut scene = Scene::new();
+    let objects = [
+        (Shape::from_parametric_curve(trefoil, 0.0..=TAU, 200), Point(-40.0, 0.0, 0.0), RGB(255, 120, 80)),
+        (Shape::from_parametric_curve(helix, 0.0..=4.0 * PI, 160), Point(0.0, 0.0, 0.0), RGB(120, 255, 120)),
+        (
+            Shape::from_parametric_surface(mobius, 0.0..=TAU, -3.0..=3.0, (48, 4)),
+            Point(40.0, 0.0, 0.0),
+            RGB(120, 160, 255),
+        ),
+    ]
+    .map(|(shape, at, color)| {
+        let node = scene.add("object", shape);
+        scene.node_mut(node).material = Material {
+            color: Some(color),
+            edges: None,
+        };
+        (node, at)
+    });
+    let mut recording = ArgsRecorder::from_args(DISPLAY_X, DISPLAY_Y, RECORD_FRAMES).expect("failed to start recording");
+    for frame in 0.. {
+        let t = frame as f64 * 0.03;
+        for (node, at) in objects {
+            scene.node_mut(node).transform = Transform::rotation((0.7, t, 0.3 * t)).then(&Transform::translation(at));
+        }
+        display.draw_scene(&scene);
+        display.print();
+
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
@@ -0,0 +1,32 @@
+use asciigraphix_core::{
+    color::{ColorDepth, Dither},
+    plot::{Plot, PlotOptions},
+    scene::Transform,
+    shapes::Point,
+    Display,
+};
+
+// The sombrero z = sin(r) / r on a turntable, with its axes and values.
+fn main() {
+    const DISPLAY_X: usize = 120;
+    const DISPLAY_Y: usize = 50;
+    const TILT: f64 = 0.5;
+    let mut display = Display::new(DISPLAY_X, DISPLAY_Y, Point(0.0, -60.0, 0.0), Point(0.0, 1.0, 0.0), 60.0);
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    let sombrero = |x: f64, y: f64| {
+        let r = (x * x + y * y).sqrt();
+        if r == 0.0 { 1.0 } else { r.sin() / r }
+    };
+    let mut plot = Plot::surface(sombrero, -10.0..=10.0, -10.0..=10.0, &PlotOptions::default());
+    // looking down on it a little, then turning it about its own z
+    plot.transform(&Transform::rotation((TILT, 0.0, 0.0)));
+    let turn = Transform::rotation((-TILT, 0.0, 0.0))
+        .then(&Transform::rotation((0.0, 0.0, 0.01)))
+        .then(&Transform::rotation((TILT, 0.0, 0.0)));
+    loop {
+        display.draw_plot(&plot);
+        display.print();
+        plot.transform(&turn);
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
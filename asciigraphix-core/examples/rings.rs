@@ -1,10 +1,15 @@
 use asciigraphix_core::{
-    shapes::{Point, Shape}, Display
+    color::{ColorDepth, Dither},
+    record::ArgsRecorder,
+    scene::{Scene, Transform},
+    shapes::{Point, Shape},
+    Display,
 };
 
 fn main() {
     const DISPLAY_X: usize = 100;
     const DISPLAY_Y: usize = 50;
+    const RECORD_FRAMES: usize = 300;
     let mut display = Display::new(
         DISPLAY_X,
         DISPLAY_Y,
@@ -12,26 +17,29 @@ fn main() {
         Point(0.0, 0.0, 1.0),
         20.0,
     );
-    let mut ring2 = Shape::generate_ring(5.0, Point(30.0, 5.0, 1.0));
-    let mut ring1 = Shape::generate_ring(10.0, Point(-10.0, 5.0, 1.0));
-    let mut ring3 = Shape::generate_ring(15.0, Point(-55.0, 5.0, 1.0));
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    // each ring turns about its own center
+    let mut scene = Scene::new();
+    let rings = [(10.0, Point(-10.0, 5.0, 1.0)), (5.0, Point(30.0, 5.0, 1.0)), (15.0, Point(-55.0, 5.0, 1.0))]
+        .map(|(radius, center)| (scene.add("ring", Shape::generate_ring(radius, center)), center));
+    let mut flat = Shape::empty();
+    let mut recording = ArgsRecorder::from_args(DISPLAY_X, DISPLAY_Y, RECORD_FRAMES).expect("failed to start recording");
     let mut b: f64 = 0.0;
     loop {
-        ring1.rotate(
-            &ring1.center.clone().unwrap_or(Point(0.0, 0.0, 0.0)),
-            (-0.1 * b.sin(), 0.1 * (1.0 - b.sin()), 0.0),
-        );
-        ring2.rotate(
-            &ring2.center.clone().unwrap_or(Point(0.0, 0.0, 0.0)),
-            // (0.1 * b.sin(), 0.1 * (1.0 - b.cos()), 0.0),
-            (-0.1 * b.sin(), 0.1 * (1.0 - b.sin()), 0.0),
-        );
-        ring3.rotate(
-            &ring3.center.clone().unwrap_or(Point(0.0, 0.0, 0.0)),
-            // (0.01, 0.01 * b.sin(), 0.0),
-            (-0.1 * b.sin(), 0.1 * (1.0 - b.sin()), 0.0),
-        );
-        display.render_print(&ring1.combine(&ring2).combine(&ring3));
+        for (ring, center) in rings {
+            let step = Transform::rotation_about(center, (-0.1 * b.sin(), 0.1 * (1.0 - b.sin()), 0.0));
+            let node = scene.node_mut(ring);
+            node.transform = node.transform.then(&step);
+        }
+        scene.flatten_into(&mut flat);
+        display.render_print(&flat);
+
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
         std::thread::sleep(std::time::Duration::from_millis(33));
         b += 0.01;
     }
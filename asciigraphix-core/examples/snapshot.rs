@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+
+use asciigraphix_core::{
+    shapes::{Point, Shape}, Display, ImageStyle
+};
+
+// Saves a frame of a rotated cube as images, or as an HTML page, e.g.
+// cargo run --example snapshot -- cube.png
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or(String::from("cube.png"));
+    let mut display = Display::new(
+        100,
+        50,
+        Point(0.0, -40.0, 0.0),
+        Point(0.0, 1.0, 0.0),
+        40.0,
+    );
+    let mut cube = Shape::generate_cube(Point(0.0, 0.0, 0.0), 14.0);
+    cube.rotate(&Point::zero(), (0.5, 0.6, 0.0));
+    display.draw(&cube);
+
+    if path.ends_with(".html") {
+        display.save_html(&path)?;
+        println!("saved {}", path);
+        return Ok(());
+    }
+
+    display.save_image(&path, ImageStyle::Text)?;
+    let pixels = pixels_path(Path::new(&path));
+    display.save_image(&pixels, ImageStyle::Pixels)?;
+    println!("saved {} and {}", path, pixels.display());
+    Ok(())
+}
+
+// cube.png -> cube-pixels.png, only the last extension counts.
+fn pixels_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}-pixels.{}", stem, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}-pixels", stem)),
+    }
+}
@@ -0,0 +1,33 @@
+use asciigraphix_core::{
+    shapes::{Point, Point4, Shape, Shape4},
+    svg::SvgStyle,
+    Display, HiddenLines,
+};
+
+// Writes cube.svg and tesseract.svg, vector figures of the projections.
+fn main() {
+    let display = Display::new(100, 100, Point(12.0, -40.0, 18.0), Point(-0.25, 1.0, -0.4), 60.0);
+
+    let mut cube = Shape::generate_cube(Point(0.0, 0.0, 0.0), 14.0);
+    cube.rotate(&Point(0.0, 0.0, 0.0), (0.3, 0.0, 0.4));
+    display
+        .save_svg(&cube, &SvgStyle::default(), "cube.svg")
+        .expect("failed to save cube.svg");
+
+    let l = 1.4;
+    let mut tesseract = Shape4::generate_4d_paralellepiped(
+        Point4(-5.0, -5.0, -5.0, -5.0) * l,
+        Point4(10.0, 0.0, 0.0, 0.0) * l,
+        Point4(0.0, 10.0, 0.0, 0.0) * l,
+        Point4(0.0, 0.0, 10.0, 0.0) * l,
+        Point4(0.0, 0.0, 0.0, 10.0) * l,
+    );
+    tesseract.rotate(&Point4::zero(), (0.0, 0.0, 0.0, 0.3, 0.2, 0.0));
+    let style = SvgStyle {
+        hidden: HiddenLines::Show,
+        ..SvgStyle::default()
+    };
+    display
+        .save_svg(&tesseract.project_to_3d(), &style, "tesseract.svg")
+        .expect("failed to save tesseract.svg");
+}
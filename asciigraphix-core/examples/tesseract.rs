@@ -1,27 +1,79 @@
+use std::io::Write;
+
 use asciigraphix_core::{
-    shapes::{Point, Point4, Shape4}, Display
+    color::{ColorDepth, Dither},
+    graphics::GraphicsProtocol,
+    record::ArgsRecorder,
+    scene::{Material, Scene},
+    shapes::{EdgeStyle, LineStyle, Point, Point4, Shape, Shape4},
+    Display, RGB,
 };
 
+// Pass --sixel or --kitty to draw the tesseract as an image on terminals that
+// support it.
 fn main() {
-    const DISPLAY_X: usize = 300;
-    const DISPLAY_Y: usize = 100;
+    const RECORD_FRAMES: usize = 300;
+    let protocol = std::env::args().find_map(|arg| match arg.as_str() {
+        "--sixel" => Some(GraphicsProtocol::Sixel),
+        "--kitty" => Some(GraphicsProtocol::Kitty),
+        _ => None,
+    });
+    // pixels are a lot smaller than characters
+    let (display_x, display_y, focal) = match protocol {
+        Some(_) => (600, 600, 80.0),
+        None => (300, 100, 20.0),
+    };
     let mut display = Display::new(
-        DISPLAY_X,
-        DISPLAY_Y,
+        display_x,
+        display_y,
         Point(0.0, -20.0, 0.0),
         Point(0.0, 1.0, 0.0),
-        20.0,
+        focal,
     );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
     let l = 2.0;
     let center = Point4(-5.0,-5.0,-5.0,-5.0)*l;
     let mut tesseract = Shape4::generate_4d_paralellepiped(center, Point4(10.0, 0.0, 0.0,0.0)*l, Point4(0.0,10.0,0.0,0.0)*l, Point4(0.0,0.0,10.0,0.0)*l, Point4(0.0,0.0,0.0,10.0)*l);
+    // the projected tesseract and the axes through its center
+    let mut scene = Scene::new();
+    let projected = scene.add("tesseract", tesseract.project_to_3d());
+    let axes = scene.add_group("axes", None);
+    for (name, axis) in [("x", Point::e(1)), ("y", Point::e(2)), ("z", Point::e(3))] {
+        let line = scene.add_child(axes, name, Shape::generate_line(axis * -15.0 * l, axis * 15.0 * l));
+        scene.node_mut(line).material = Material {
+            color: Some(RGB(120, 120, 120)),
+            edges: Some(EdgeStyle {
+                line: LineStyle::Dotted,
+                priority: 0,
+            }),
+        };
+    }
+    let mut flat = Shape::empty();
+    let mut recording = ArgsRecorder::from_args(display_x, display_y, RECORD_FRAMES).expect("failed to start recording");
 
     // tesseract.rotate(&Point4(0.0, 0.0, 0.0, 0.0), (0.0, 0.0, 0.0, 0.52, 0.51, 0.00));
     loop {
         tesseract.rotate(&Point4(0.0, 0.0, 0.0, 0.0), (0.0, 0.0, 0.0, 0.02, 0.00, 0.00));
         // tesseract.rotate(&Point4(0.0, 0.0, 0.0, 0.0), (0.0, 0.0, 0.0, 0.00, 0.00, 0.00));
+        tesseract.project_to_3d_into(scene.node_mut(projected).shape_mut());
+        scene.flatten_into(&mut flat);
+
+        match protocol {
+            Some(protocol) => {
+                let mut stdout = std::io::stdout().lock();
+                display
+                    .render_graphics(&flat, protocol, (1, 1), &mut stdout)
+                    .and_then(|_| stdout.flush())
+                    .expect("failed writing to stdout");
+            }
+            None => display.render_print(&flat),
+        }
 
-        display.render_print(&tesseract.project_to_3d());
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
 
         std::thread::sleep(std::time::Duration::from_millis(33));
     }
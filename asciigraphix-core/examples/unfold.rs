@@ -0,0 +1,45 @@
+use asciigraphix_core::{
+    color::{ColorDepth, Dither},
+    record::ArgsRecorder,
+    shapes::{Point, Point4, Shape4},
+    Display,
+};
+
+// Unfolds a tesseract into its net of eight cubes and folds it back. A
+// recording takes one round of it.
+fn main() {
+    const FRAMES: usize = 240;
+    const SIZE: f64 = 6.0;
+    let (display_x, display_y) = (160, 60);
+    let mut display = Display::new(
+        display_x,
+        display_y,
+        Point(0.0, -45.0, 0.0),
+        Point(0.0, 1.0, 0.0),
+        30.0,
+    );
+    display.set_color_depth(ColorDepth::detect(), Dither::Ordered);
+    let mut recording = ArgsRecorder::from_args(display_x, display_y, FRAMES).expect("failed to start recording");
+    let center = Point4(0.0, 0.0, SIZE / 2.0, 0.0);
+    let mut net = Shape4::unfold_tesseract(center, SIZE, 0.0);
+    let mut projected = net.project_to_3d();
+
+    for frame in 0.. {
+        // there and back again
+        let phase = (frame % FRAMES) as f64 / FRAMES as f64;
+        let t = 1.0 - (2.0 * phase - 1.0).abs();
+        let turn = frame as f64 * 0.01;
+        Shape4::unfold_tesseract_into(&mut net, center, SIZE, t);
+        net.rotate(&Point4::zero(), (0.0, 0.3, turn, 0.0, 0.0, 0.0));
+        net.project_to_3d_into(&mut projected);
+        display.render_print(&projected);
+
+        if let Some(recorder) = &mut recording
+            && recorder.frame_or_done(display.cells()).expect("failed to record frame")
+        {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use crate::{
+    color::{ColorDepth, Dither, Quantizer},
+    Cell, RGB,
+};
+
+// Turns a framebuffer of cells into ANSI escape sequences.
+//
+// Consecutive cells sharing a style are written as a single run. In
+// incremental mode only the cells that changed since the previous frame are
+// sent, which keeps the output small when little moves on screen.
+//
+// Colors are reduced to the configured color depth before comparing, so
+// changes the terminal couldn't show anyway aren't sent either.
+pub struct AnsiEncoder {
+    incremental: bool,
+    width: usize,
+    quantizer: Quantizer,
+    current: Vec<Cell>,
+    previous: Vec<Cell>,
+    // the escape sequences of the last frame, reused by `encode`
+    output: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Style {
+    fg: Option<RGB>,
+    bg: Option<RGB>,
+    bold: bool,
+}
+
+impl From<&Cell> for Style {
+    fn from(cell: &Cell) -> Self {
+        Style {
+            fg: cell.fg,
+            bg: cell.bg,
+            bold: cell.bold,
+        }
+    }
+}
+
+impl Default for AnsiEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiEncoder {
+    // Redraws every cell on every frame.
+    pub fn new() -> Self {
+        Self {
+            incremental: false,
+            width: 0,
+            quantizer: Quantizer::new(ColorDepth::TrueColor, Dither::None),
+            current: Vec::new(),
+            previous: Vec::new(),
+            output: String::new(),
+        }
+    }
+
+    // Only sends the cells that differ from the previous frame.
+    pub fn incremental() -> Self {
+        Self {
+            incremental: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn set_color_depth(&mut self, depth: ColorDepth, dither: Dither) {
+        self.quantizer = Quantizer::new(depth, dither);
+        self.reset();
+    }
+
+    pub fn color_depth(&self) -> ColorDepth {
+        self.quantizer.depth()
+    }
+
+    // Forget the previous frame, the next one is drawn from scratch.
+    pub fn reset(&mut self) {
+        self.previous.clear();
+    }
+
+    pub fn encode<W: Write>(&mut self, cells: &[Cell], width: usize, out: &mut W) -> io::Result<()> {
+        let mut output = std::mem::take(&mut self.output);
+        output.clear();
+        self.encode_into(cells, width, &mut output);
+        let written = out.write_all(output.as_bytes());
+        self.output = output;
+        written
+    }
+
+    pub fn encode_to_string(&mut self, cells: &[Cell], width: usize) -> String {
+        let mut buf = String::new();
+        self.encode_into(cells, width, &mut buf);
+        buf
+    }
+
+    fn encode_into(&mut self, cells: &[Cell], width: usize, out: &mut String) {
+        let width = width.max(1);
+        self.current.clear();
+        self.current.extend_from_slice(cells);
+        self.quantizer.quantize(&mut self.current, width);
+        let depth = self.quantizer.depth();
+
+        let redraw = self.previous.len() != cells.len() || self.width != width;
+        if redraw {
+            // clear screen, hide cursor
+            out.push_str("\x1B[2J\x1B[?25l");
+        }
+
+        let mut style: Option<Style> = None;
+        // where the cursor is after the last written cell
+        let mut cursor: Option<usize> = None;
+        for (i, cell) in self.current.iter().enumerate() {
+            if self.incremental && !redraw && self.previous[i] == *cell {
+                continue;
+            }
+            if cursor != Some(i) {
+                let _ = write!(out, "\x1B[{};{}H", i / width + 1, i % width + 1);
+            }
+            let cell_style = Style::from(cell);
+            if style != Some(cell_style) {
+                push_sgr(out, &cell_style, depth);
+                style = Some(cell_style);
+            }
+            out.push(cell.glyph);
+            // don't rely on the terminal wrapping at the end of a row
+            cursor = Some(i + 1).filter(|next| next % width != 0);
+        }
+        if style.is_some() {
+            out.push_str("\x1B[0m");
+        }
+
+        self.width = width;
+        std::mem::swap(&mut self.previous, &mut self.current);
+    }
+}
+
+fn push_sgr(out: &mut String, style: &Style, depth: ColorDepth) {
+    out.push_str("\x1B[0");
+    if style.bold {
+        out.push_str(";1");
+    }
+    if let Some(color) = style.fg {
+        push_color(out, color, depth, false);
+    }
+    if let Some(color) = style.bg {
+        push_color(out, color, depth, true);
+    }
+    out.push('m');
+}
+
+fn push_color(out: &mut String, color: RGB, depth: ColorDepth, background: bool) {
+    match (depth, depth.index(color)) {
+        (ColorDepth::TrueColor, _) => {
+            let RGB(r, g, b) = color;
+            let _ = write!(out, ";{}8;2;{};{};{}", if background { 4 } else { 3 }, r, g, b);
+        }
+        (ColorDepth::Ansi256, Some(i)) => {
+            let _ = write!(out, ";{}8;5;{}", if background { 4 } else { 3 }, i);
+        }
+        (ColorDepth::Ansi16, Some(i)) => {
+            let base = match (background, i < 8) {
+                (false, true) => 30,
+                (false, false) => 90 - 8,
+                (true, true) => 40,
+                (true, false) => 100 - 8,
+            };
+            let _ = write!(out, ";{}", base + i as u16);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::{ColorDepth, Dither},
+        Cell, RGB,
+    };
+
+    use super::AnsiEncoder;
+
+    fn cell(glyph: char, fg: RGB) -> Cell {
+        Cell {
+            glyph,
+            fg: Some(fg),
+            ..Cell::EMPTY
+        }
+    }
+
+    #[test]
+    fn runs() {
+        let red = RGB(255, 0, 0);
+        let cells = [cell('a', red), cell('b', red), Cell::EMPTY, Cell::EMPTY];
+        let out = AnsiEncoder::new().encode_to_string(&cells, 2);
+        assert_eq!(
+            out,
+            "\x1B[2J\x1B[?25l\x1B[1;1H\x1B[0;38;2;255;0;0mab\x1B[2;1H\x1B[0m  \x1B[0m"
+        );
+    }
+
+    #[test]
+    fn incremental() {
+        let red = RGB(255, 0, 0);
+        let mut encoder = AnsiEncoder::incremental();
+        let mut cells = [Cell::EMPTY; 4];
+        encoder.encode_to_string(&cells, 2);
+        assert_eq!(encoder.encode_to_string(&cells, 2), "");
+
+        cells[3] = cell('x', red);
+        assert_eq!(
+            encoder.encode_to_string(&cells, 2),
+            "\x1B[2;2H\x1B[0;38;2;255;0;0mx\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn color_depth() {
+        let mut encoder = AnsiEncoder::new();
+        let cells = [cell('a', RGB(250, 10, 10)), cell('b', RGB(0, 0, 250))];
+        encoder.set_color_depth(ColorDepth::Ansi16, Dither::None);
+        assert!(encoder.encode_to_string(&cells, 2).ends_with("\x1B[0;91ma\x1B[0;34mb\x1B[0m"));
+        encoder.set_color_depth(ColorDepth::Ansi256, Dither::None);
+        assert!(encoder
+            .encode_to_string(&cells, 2)
+            .ends_with("\x1B[0;38;5;196ma\x1B[0;38;5;21mb\x1B[0m"));
+        encoder.set_color_depth(ColorDepth::Mono, Dither::None);
+        assert!(encoder.encode_to_string(&cells, 2).ends_with("\x1B[0mab\x1B[0m"));
+    }
+}
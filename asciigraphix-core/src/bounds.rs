@@ -0,0 +1,249 @@
+use crate::{
+    shapes::{Point, Point4, Shape, Shape4},
+    Display,
+};
+
+// Smallest box with sides along the axes around a shape.
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb4 {
+    pub min: Point4,
+    pub max: Point4,
+}
+
+// A sphere around all of a shape, close to but not always the smallest.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Point,
+    pub radius: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere4 {
+    pub center: Point4,
+    pub radius: f64,
+}
+
+impl Aabb {
+    pub fn center(&self) -> Point {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Point {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        (self.min.0..=self.max.0).contains(&p.0)
+            && (self.min.1..=self.max.1).contains(&p.1)
+            && (self.min.2..=self.max.2).contains(&p.2)
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point(self.min.0.min(other.min.0), self.min.1.min(other.min.1), self.min.2.min(other.min.2)),
+            max: Point(self.max.0.max(other.max.0), self.max.1.max(other.max.1), self.max.2.max(other.max.2)),
+        }
+    }
+
+    // By bits of the index, x first, 0 for min and 1 for max.
+    pub fn corners(&self) -> [Point; 8] {
+        let (a, b) = (self.min, self.max);
+        std::array::from_fn(|i| {
+            let pick = |bit: usize, lo: f64, hi: f64| if i >> bit & 1 == 0 { lo } else { hi };
+            Point(pick(0, a.0, b.0), pick(1, a.1, b.1), pick(2, a.2, b.2))
+        })
+    }
+}
+
+impl Aabb4 {
+    pub fn center(&self) -> Point4 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Point4 {
+        self.max - self.min
+    }
+}
+
+impl BoundingSphere {
+    pub fn contains(&self, p: &Point) -> bool {
+        (*p - self.center).magnitude() <= self.radius
+    }
+}
+
+fn coordinates(p: &Point) -> [f64; 3] {
+    [p.0, p.1, p.2]
+}
+
+fn coordinates4(p: &Point4) -> [f64; 4] {
+    [p.0, p.1, p.2, p.3]
+}
+
+// These go over the points as they are, so a shape's vertices don't need
+// copying first. `sphere` goes over them a few times.
+fn bounds<const N: usize>(mut points: impl Iterator<Item = [f64; N]>) -> Option<([f64; N], [f64; N])> {
+    let first = points.next()?;
+    Some(points.fold((first, first), |(mut min, mut max), p| {
+        for k in 0..N {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+        (min, max)
+    }))
+}
+
+fn centroid<const N: usize>(points: impl Iterator<Item = [f64; N]>) -> Option<[f64; N]> {
+    let mut sum = [0.0; N];
+    let mut count = 0;
+    for p in points {
+        for k in 0..N {
+            sum[k] += p[k];
+        }
+        count += 1;
+    }
+    (count > 0).then(|| sum.map(|s| s / count as f64))
+}
+
+fn distance<const N: usize>(a: &[f64; N], b: &[f64; N]) -> f64 {
+    (0..N).map(|k| (a[k] - b[k]).powi(2)).sum::<f64>().sqrt()
+}
+
+// Ritter's: a sphere through the two points furthest apart that a couple of
+// passes can find, then grown to take in whatever is still outside.
+fn sphere<const N: usize>(points: impl Iterator<Item = [f64; N]> + Clone) -> Option<([f64; N], f64)> {
+    let first = points.clone().next()?;
+    let furthest = |from: &[f64; N]| {
+        points.clone().fold(*from, |far, p| if distance(from, &p) > distance(from, &far) { p } else { far })
+    };
+    let a = furthest(&first);
+    let b = furthest(&a);
+    let mut center: [f64; N] = std::array::from_fn(|k| (a[k] + b[k]) / 2.0);
+    let mut radius = distance(&a, &b) / 2.0;
+    for p in points {
+        let d = distance(&center, &p);
+        if d > radius {
+            // move the center towards p just enough to reach it
+            let grown = (radius + d) / 2.0;
+            for k in 0..N {
+                center[k] += (p[k] - center[k]) * (grown - radius) / d;
+            }
+            radius = grown;
+        }
+    }
+    Some((center, radius))
+}
+
+impl Shape {
+    pub fn aabb(&self) -> Option<Aabb> {
+        bounds(self.vertices.iter().map(coordinates)).map(|(min, max)| Aabb {
+            min: Point(min[0], min[1], min[2]),
+            max: Point(max[0], max[1], max[2]),
+        })
+    }
+
+    // The average of the vertices, unlike `center` always where the shape
+    // is now.
+    pub fn centroid(&self) -> Option<Point> {
+        centroid(self.vertices.iter().map(coordinates)).map(|c| Point(c[0], c[1], c[2]))
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        sphere(self.vertices.iter().map(coordinates)).map(|(c, radius)| BoundingSphere {
+            center: Point(c[0], c[1], c[2]),
+            radius,
+        })
+    }
+}
+
+impl Shape4 {
+    pub fn aabb(&self) -> Option<Aabb4> {
+        bounds(self.vertices.iter().map(coordinates4)).map(|(min, max)| Aabb4 {
+            min: Point4(min[0], min[1], min[2], min[3]),
+            max: Point4(max[0], max[1], max[2], max[3]),
+        })
+    }
+
+    pub fn centroid(&self) -> Option<Point4> {
+        centroid(self.vertices.iter().map(coordinates4)).map(|c| Point4(c[0], c[1], c[2], c[3]))
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere4> {
+        sphere(self.vertices.iter().map(coordinates4)).map(|(c, radius)| BoundingSphere4 {
+            center: Point4(c[0], c[1], c[2], c[3]),
+            radius,
+        })
+    }
+}
+
+impl Display {
+    // Moves the camera back along the way it looks until the whole shape
+    // fits in the smaller side of the display with a little room to spare.
+    // Returns `false`, leaving the camera alone, for an empty shape.
+    pub fn fit_camera(&mut self, shape: &Shape) -> bool {
+        // how much of the display the shape gets
+        const FILL: f64 = 0.9;
+        let Some(sphere) = shape.bounding_sphere() else {
+            return false;
+        };
+        let half = self.x_size.min(self.y_size) as f64 / 2.0 * FILL;
+        // far enough that the sphere's outline, where the view touches it,
+        // is `half` from the middle of the screen
+        let distance = match half > 0.0 {
+            true => sphere.radius * (1.0 + (self.cam_focal / half).powi(2)).sqrt(),
+            false => sphere.radius,
+        };
+        let direction = self.cam_unit_vectors.0;
+        self.cam_pos = sphere.center - direction * distance.max(f64::EPSILON);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shapes::{Point, Point4, Shape, Shape4},
+        Display,
+    };
+
+    #[test]
+    fn bounds() {
+        let corner = Point(1.0, 2.0, 3.0);
+        let box_ = Shape::generate_parallelepiped(corner, Point::e(1) * 2.0, Point::e(2) * 4.0, Point::e(3) * 6.0);
+        let aabb = box_.aabb().unwrap();
+        assert_eq!(aabb.size().magnitude(), Point(2.0, 4.0, 6.0).magnitude());
+        let center = box_.centroid().unwrap();
+        assert!((center - Point(2.0, 4.0, 6.0)).magnitude() < 1e-9);
+        assert!((box_.center.unwrap() - center).magnitude() < 1e-9);
+        assert!(aabb.corners().iter().all(|c| aabb.contains(c)));
+
+        let sphere = box_.bounding_sphere().unwrap();
+        assert!(box_.vertices.iter().all(|v| sphere.contains(v)));
+        // the half diagonal, as small as it gets
+        assert!((sphere.radius - Point(1.0, 2.0, 3.0).magnitude()).abs() < 1e-9);
+        assert!(Shape::empty().aabb().is_none() && Shape::empty().bounding_sphere().is_none());
+
+        let cell = Shape4::generate_16_cell(Point4(1.0, 0.0, 0.0, 0.0), 2.0);
+        assert!((cell.bounding_sphere().unwrap().radius - 2.0).abs() < 1e-9);
+        assert!((cell.aabb().unwrap().center() - Point4(1.0, 0.0, 0.0, 0.0)).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn fit_camera() {
+        let cube = Shape::generate_cube(Point(50.0, 0.0, 0.0), 10.0);
+        let mut display = Display::new(40, 20, Point(0.0, -1000.0, 0.0), Point::e(2), 20.0);
+        assert!(display.fit_camera(&cube));
+        display.draw(&cube);
+        let filled: Vec<usize> = (0..40 * 20).filter(|i| display.cells()[*i].glyph != ' ').collect();
+        let rows: Vec<usize> = filled.iter().map(|i| i / 40).collect();
+        // fills most of the height without touching the edges
+        let (top, bottom) = (*rows.iter().min().unwrap(), *rows.iter().max().unwrap());
+        assert!(top > 0 && bottom < 19 && bottom - top >= 10);
+        assert!(!display.fit_camera(&Shape::empty()));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{Cell, RGB};
+
+// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Mono,
+}
+
+impl ColorDepth {
+    // Guess from the environment of the current process.
+    pub fn detect() -> Self {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Self::Mono;
+        }
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(colorterm.as_deref(), term.as_deref())
+    }
+
+    // Guess from the values of $COLORTERM and $TERM, e.g. the ones an ssh client
+    // sends with its pty request.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+            return Self::TrueColor;
+        }
+        let term = match term {
+            None | Some("") | Some("dumb") => return Self::Mono,
+            Some(term) => term,
+        };
+        if term.ends_with("-direct")
+            || ["kitty", "alacritty", "wezterm", "foot"]
+                .iter()
+                .any(|t| term.contains(t))
+        {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if term.starts_with("vt1") || term.starts_with("vt2") {
+            Self::Mono
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    // Palette index of the closest color, `None` when colors are passed
+    // through as is or not shown at all.
+    pub fn index(&self, color: RGB) -> Option<u8> {
+        match self {
+            Self::Ansi256 => Some(nearest_256(color)),
+            Self::Ansi16 => Some(nearest_16(color)),
+            Self::TrueColor | Self::Mono => None,
+        }
+    }
+
+    // Roughly the distance between neighbouring palette colors, used to scale
+    // the dither pattern.
+    fn spread(&self) -> f32 {
+        match self {
+            Self::TrueColor => 0.0,
+            Self::Ansi256 => 40.0,
+            Self::Ansi16 => 128.0,
+            Self::Mono => 64.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    None,
+    // 4x4 Bayer matrix, stable between frames
+    Ordered,
+    // Floyd-Steinberg
+    ErrorDiffusion,
+}
+
+// xterm defaults
+pub const ANSI_16: [RGB; 16] = [
+    RGB(0, 0, 0),
+    RGB(205, 0, 0),
+    RGB(0, 205, 0),
+    RGB(205, 205, 0),
+    RGB(0, 0, 238),
+    RGB(205, 0, 205),
+    RGB(0, 205, 205),
+    RGB(229, 229, 229),
+    RGB(127, 127, 127),
+    RGB(255, 0, 0),
+    RGB(0, 255, 0),
+    RGB(255, 255, 0),
+    RGB(92, 92, 255),
+    RGB(255, 0, 255),
+    RGB(0, 255, 255),
+    RGB(255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+pub fn ansi_256(index: u8) -> RGB {
+    match index {
+        0..16 => ANSI_16[index as usize],
+        16..232 => {
+            let i = index - 16;
+            RGB(
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        232.. => {
+            let v = 8 + 10 * (index - 232);
+            RGB(v, v, v)
+        }
+    }
+}
+
+fn distance(a: RGB, b: RGB) -> i32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    dr * dr + dg * dg + db * db
+}
+
+fn nearest_16(color: RGB) -> u8 {
+    (0..16u8)
+        .min_by_key(|i| distance(color, ANSI_16[*i as usize]))
+        .unwrap_or(0)
+}
+
+fn nearest_256(color: RGB) -> u8 {
+    let level = |v: u8| {
+        (0..6)
+            .min_by_key(|i| (CUBE_LEVELS[*i] as i32 - v as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * level(color.0) + 6 * level(color.1) + level(color.2);
+
+    let average = (color.0 as u32 + color.1 as u32 + color.2 as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(3) / 10).min(23)) as u8;
+
+    if distance(color, ansi_256(gray)) < distance(color, ansi_256(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn luminance(color: RGB) -> f32 {
+    0.2126 * color.0 as f32 + 0.7152 * color.1 as f32 + 0.0722 * color.2 as f32
+}
+
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Reduces the foreground colors of a framebuffer to what a terminal of the
+// given depth can show. Background colors are mapped to their nearest palette
+// color without dithering.
+//
+// Afterwards every color is exactly a palette color, so an encoder can look up
+// its index without any loss. In `Mono` colors are dropped, bright cells are
+// drawn bold instead and black ones are left blank.
+pub struct Quantizer {
+    depth: ColorDepth,
+    dither: Dither,
+    // error carried over to not yet visited cells
+    errors: Vec<[f32; 3]>,
+}
+
+impl Quantizer {
+    pub fn new(depth: ColorDepth, dither: Dither) -> Self {
+        Self {
+            depth,
+            dither,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn depth(&self) -> ColorDepth {
+        self.depth
+    }
+
+    pub fn dither(&self) -> Dither {
+        self.dither
+    }
+
+    pub fn quantize(&mut self, cells: &mut [Cell], width: usize) {
+        if self.depth == ColorDepth::TrueColor {
+            return;
+        }
+        let width = width.max(1);
+        if self.dither == Dither::ErrorDiffusion {
+            self.errors.clear();
+            self.errors.resize(cells.len(), [0.0; 3]);
+        }
+        for (i, cell) in cells.iter_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            if let Some(bg) = cell.bg {
+                cell.bg = self.nearest(bg).0;
+            }
+            let Some(fg) = cell.fg else {
+                continue;
+            };
+            let mut wanted = [fg.0 as f32, fg.1 as f32, fg.2 as f32];
+            match self.dither {
+                Dither::None => {}
+                Dither::Ordered => {
+                    let offset = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                    for c in &mut wanted {
+                        *c += offset * self.depth.spread();
+                    }
+                }
+                Dither::ErrorDiffusion => {
+                    for (c, e) in wanted.iter_mut().zip(self.errors[i]) {
+                        *c += e;
+                    }
+                }
+            }
+            let clamped = RGB(
+                wanted[0].clamp(0.0, 255.0) as u8,
+                wanted[1].clamp(0.0, 255.0) as u8,
+                wanted[2].clamp(0.0, 255.0) as u8,
+            );
+            let (color, bold, shown) = self.nearest(clamped);
+            cell.fg = color;
+            cell.bold |= bold;
+            if self.depth == ColorDepth::Mono && shown == RGB(0, 0, 0) {
+                // black text, i.e. nothing to see
+                cell.glyph = ' ';
+            }
+
+            if self.dither == Dither::ErrorDiffusion {
+                let error = [
+                    wanted[0] - shown.0 as f32,
+                    wanted[1] - shown.1 as f32,
+                    wanted[2] - shown.2 as f32,
+                ];
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    if nx < 0 || nx >= width as isize {
+                        return;
+                    }
+                    let j = (y + dy) * width + nx as usize;
+                    if let Some(e) = self.errors.get_mut(j) {
+                        for (e, v) in e.iter_mut().zip(error) {
+                            *e += v * weight;
+                        }
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+
+    // (new color, bold, the color as it will look)
+    fn nearest(&self, color: RGB) -> (Option<RGB>, bool, RGB) {
+        match self.depth {
+            ColorDepth::TrueColor => (Some(color), false, color),
+            ColorDepth::Ansi256 => {
+                let shown = ansi_256(nearest_256(color));
+                (Some(shown), false, shown)
+            }
+            ColorDepth::Ansi16 => {
+                let shown = ANSI_16[nearest_16(color) as usize];
+                (Some(shown), false, shown)
+            }
+            ColorDepth::Mono => {
+                let l = luminance(color);
+                if l < 16.0 {
+                    (None, false, RGB(0, 0, 0))
+                } else if l < 192.0 {
+                    (None, false, RGB(170, 170, 170))
+                } else {
+                    (None, true, RGB(255, 255, 255))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cell, RGB};
+
+    use super::{ansi_256, ColorDepth, Dither, Quantizer};
+
+    #[test]
+    fn detect() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-kitty")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("dumb")), ColorDepth::Mono);
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Mono);
+    }
+
+    #[test]
+    fn palette() {
+        for i in 16..=255 {
+            assert_eq!(ColorDepth::Ansi256.index(ansi_256(i)).map(ansi_256), Some(ansi_256(i)));
+        }
+    }
+
+    #[test]
+    fn dithered_gradient() {
+        // a color halfway between two palette colors should come out as a mix
+        // of both rather than a flat area
+        let fill = Cell {
+            glyph: '#',
+            fg: Some(RGB(115, 0, 0)),
+            ..Cell::EMPTY
+        };
+        for dither in [Dither::Ordered, Dither::ErrorDiffusion] {
+            let mut cells = [fill; 16];
+            Quantizer::new(ColorDepth::Ansi256, dither).quantize(&mut cells, 4);
+            let reds: Vec<u8> = cells.iter().map(|c| c.fg.unwrap().0).collect();
+            assert!(reds.contains(&95) && reds.contains(&135), "{dither:?}: {reds:?}");
+        }
+    }
+}
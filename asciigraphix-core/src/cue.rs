@@ -0,0 +1,190 @@
+use crate::RGB;
+
+// How things fade with distance, in color as well as in the glyphs used for
+// them. Nothing fades before `near` and fading is complete at `far`. By
+// default only the glyphs change, at depths 10, 30 and 50, and fog and bold
+// are opt-in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthCue {
+    pub falloff: Falloff,
+    pub near: f64,
+    pub far: f64,
+    // what colors fade into
+    pub fog: RGB,
+    // how much fog there is at `far`, 0.0 to 1.0
+    pub max_fog: f64,
+    // anything nearer is bold
+    pub bold_within: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Falloff {
+    Linear,
+    // fades quickly right after `near`
+    Exponential,
+    // fades slowly at first, then quickly
+    Exponential2,
+}
+
+impl Default for DepthCue {
+    fn default() -> Self {
+        Self {
+            falloff: Falloff::Linear,
+            near: 0.0,
+            far: 50.0,
+            fog: RGB(0, 0, 0),
+            max_fog: 0.0,
+            bold_within: None,
+        }
+    }
+}
+
+impl DepthCue {
+    // Glyphs from near to far, and how far along each one ends. Only what is
+    // past `far` gets the last one.
+    const RAMP: [char; 4] = ['#', '*', '-', '.'];
+    const STEPS: [f64; 3] = [0.2, 0.6, 1.0];
+
+    // How far along from `near` to `far` a depth is, 0.0 to 1.0 shaped by the
+    // falloff.
+    pub fn distance(&self, depth: f64) -> f64 {
+        // the exponential curves are scaled to reach 1.0 at `far`
+        const DENSITY: f64 = 3.0;
+        let t = if self.far > self.near {
+            ((depth - self.near) / (self.far - self.near)).clamp(0.0, 1.0)
+        } else if depth < self.near {
+            0.0
+        } else {
+            1.0
+        };
+        match self.falloff {
+            Falloff::Linear => t,
+            Falloff::Exponential => (1.0 - (-DENSITY * t).exp()) / (1.0 - (-DENSITY).exp()),
+            Falloff::Exponential2 => (1.0 - (-(DENSITY * t).powi(2)).exp()) / (1.0 - (-DENSITY.powi(2)).exp()),
+        }
+    }
+
+    pub fn fog_amount(&self, depth: f64) -> f64 {
+        self.distance(depth) * self.max_fog.clamp(0.0, 1.0)
+    }
+
+    // The color as seen through the fog at that depth.
+    pub fn apply(&self, color: RGB, depth: f64) -> RGB {
+        let amount = self.fog_amount(depth);
+        let mix = |c: u8, f: u8| (c as f64 + (f as f64 - c as f64) * amount).round() as u8;
+        RGB(
+            mix(color.0, self.fog.0),
+            mix(color.1, self.fog.1),
+            mix(color.2, self.fog.2),
+        )
+    }
+
+    // # * - . from near to far.
+    pub fn glyph(&self, depth: f64) -> char {
+        let distance = self.distance(depth);
+        Self::RAMP[Self::STEPS.iter().filter(|s| distance >= **s).count()]
+    }
+
+    pub fn bold(&self, depth: f64) -> bool {
+        self.bold_within.is_some_and(|d| depth < d)
+    }
+}
+
+// Colors for the 4th coordinate of shapes projected from 4D, going from
+// `negative` at -w through `positive` at +w.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WGradient {
+    pub negative: RGB,
+    pub positive: RGB,
+    // the w that is fully `positive`, by default the largest |w| in the shape
+    pub range: Option<f64>,
+}
+
+impl Default for WGradient {
+    fn default() -> Self {
+        Self {
+            negative: RGB(40, 100, 255),
+            positive: RGB(255, 50, 40),
+            range: None,
+        }
+    }
+}
+
+impl WGradient {
+    pub fn color(&self, w: f64, range: f64) -> RGB {
+        let t = if range > 0.0 { (w / range * 0.5 + 0.5).clamp(0.0, 1.0) } else { 0.5 };
+        let mix = |n: u8, p: u8| (n as f64 + (p as f64 - n as f64) * t).round() as u8;
+        RGB(
+            mix(self.negative.0, self.positive.0),
+            mix(self.negative.1, self.positive.1),
+            mix(self.negative.2, self.positive.2),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RGB;
+
+    use super::{DepthCue, Falloff, WGradient};
+
+    #[test]
+    fn falloffs() {
+        let cue = |falloff| DepthCue {
+            falloff,
+            near: 10.0,
+            far: 20.0,
+            ..DepthCue::default()
+        };
+        for falloff in [Falloff::Linear, Falloff::Exponential, Falloff::Exponential2] {
+            let cue = cue(falloff);
+            assert_eq!(cue.distance(5.0), 0.0);
+            assert!((cue.distance(20.0) - 1.0).abs() < 1e-9);
+            assert_eq!(cue.distance(30.0), cue.distance(20.0));
+        }
+        let early = |falloff| cue(falloff).distance(11.0);
+        assert!(early(Falloff::Exponential) > early(Falloff::Linear));
+        assert!(early(Falloff::Exponential2) < early(Falloff::Linear));
+        assert_eq!(cue(Falloff::Linear).glyph(11.0), '#');
+        assert_eq!(cue(Falloff::Linear).glyph(16.0), '-');
+        assert_eq!(cue(Falloff::Linear).glyph(25.0), '.');
+    }
+
+    #[test]
+    fn default_cue() {
+        let cue = DepthCue::default();
+        let glyphs: String = [-5.0, 9.9, 10.0, 29.9, 30.0, 49.9, 50.0, 500.0].map(|d| cue.glyph(d)).iter().collect();
+        assert_eq!(glyphs, "##**--..");
+        assert_eq!(cue.apply(RGB(200, 0, 100), 500.0), RGB(200, 0, 100));
+        assert!(!cue.bold(0.0));
+    }
+
+    #[test]
+    fn fog() {
+        let cue = DepthCue {
+            near: 30.0,
+            far: 100.0,
+            fog: RGB(100, 100, 100),
+            max_fog: 1.0,
+            bold_within: Some(60.0),
+            ..DepthCue::default()
+        };
+        assert_eq!(cue.apply(RGB(200, 0, 100), 0.0), RGB(200, 0, 100));
+        assert_eq!(cue.apply(RGB(200, 0, 100), 65.0), RGB(150, 50, 100));
+        assert_eq!(cue.apply(RGB(200, 0, 100), 1000.0), RGB(100, 100, 100));
+        assert!(cue.bold(59.0) && !cue.bold(60.0));
+    }
+
+    #[test]
+    fn w_gradient() {
+        let gradient = WGradient {
+            negative: RGB(0, 0, 200),
+            positive: RGB(200, 0, 0),
+            range: None,
+        };
+        assert_eq!(gradient.color(-2.0, 2.0), RGB(0, 0, 200));
+        assert_eq!(gradient.color(0.0, 2.0), RGB(100, 0, 100));
+        assert_eq!(gradient.color(5.0, 2.0), RGB(200, 0, 0));
+        assert_eq!(gradient.color(1.0, 0.0), RGB(100, 0, 100));
+    }
+}
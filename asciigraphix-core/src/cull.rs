@@ -0,0 +1,70 @@
+use crate::{shapes::Point, Display};
+
+// Counts from the last frame drawn, to see what a scene costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    // shapes or instances drawn
+    pub objects: usize,
+    // ones skipped for being out of view
+    pub culled: usize,
+    pub vertices: usize,
+    pub edges: usize,
+    pub faces: usize,
+    // points along edges that were projected
+    pub samples: usize,
+}
+
+impl Display {
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    // Whether any of a sphere could be in view, false only when it is all
+    // behind the camera or past a side of the screen.
+    pub fn sphere_in_view(&self, center: &Point, radius: f64) -> bool {
+        let (x, y, depth) = self.view(center);
+        if depth < -radius {
+            return false;
+        }
+        // a plane through the camera and a side of the screen, with half a
+        // cell to spare for rounding
+        let outside = |across: f64, size: usize| {
+            let slope = (size as f64 / 2.0 + 0.5) / self.cam_focal;
+            (across.abs() - slope * depth) / (1.0 + slope * slope).sqrt() > radius
+        };
+        !outside(x, self.x_size) && !outside(y, self.y_size)
+    }
+
+    // About how many cells a sphere's radius takes on screen, infinite when
+    // the camera is inside it.
+    pub fn projected_radius(&self, center: &Point, radius: f64) -> f64 {
+        let depth = self.view(center).2;
+        if depth <= radius {
+            return f64::INFINITY;
+        }
+        self.cam_focal * radius / depth
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shapes::Point, Display};
+
+    #[test]
+    fn frustum() {
+        let display = Display::new(40, 20, Point::zero(), Point::e(2), 20.0);
+        assert!(display.sphere_in_view(&Point(0.0, 50.0, 0.0), 1.0));
+        // behind the camera, unless it reaches past it
+        assert!(!display.sphere_in_view(&Point(0.0, -50.0, 0.0), 1.0));
+        assert!(display.sphere_in_view(&Point(0.0, -5.0, 0.0), 10.0));
+        // the screen is 20 cells either side at focal 20, so 45 degrees
+        assert!(!display.sphere_in_view(&Point(80.0, 50.0, 0.0), 1.0));
+        assert!(display.sphere_in_view(&Point(80.0, 50.0, 0.0), 30.0));
+        // and half as high
+        assert!(!display.sphere_in_view(&Point(0.0, 50.0, 40.0), 1.0));
+        assert!(display.sphere_in_view(&Point(0.0, 50.0, 20.0), 1.0));
+
+        assert_eq!(display.projected_radius(&Point(0.0, 40.0, 0.0), 2.0), 1.0);
+        assert!(display.projected_radius(&Point(0.0, 1.0, 0.0), 2.0).is_infinite());
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Write};
+
+use crate::{color::ColorDepth, raster::Raster};
+
+// Terminal protocols that show real images instead of characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Sixel,
+    Kitty,
+}
+
+impl GraphicsProtocol {
+    pub fn encode<W: Write>(&self, raster: &Raster, out: &mut W) -> io::Result<()> {
+        match self {
+            Self::Sixel => sixel(raster, out),
+            Self::Kitty => kitty(raster, 1, out),
+        }
+    }
+}
+
+// Sixel images are palette based, colors are reduced to the 256 color xterm
+// palette first.
+pub fn sixel<W: Write>(raster: &Raster, out: &mut W) -> io::Result<()> {
+    let indices: Vec<u8> = raster
+        .pixels
+        .iter()
+        .map(|c| ColorDepth::Ansi256.index(*c).unwrap_or(0))
+        .collect();
+    let mut used = [false; 256];
+    for i in &indices {
+        used[*i as usize] = true;
+    }
+
+    let mut buf = String::new();
+    buf.push_str(&format!("\x1BPq\"1;1;{};{}", raster.width, raster.height));
+    for (i, _) in used.iter().enumerate().filter(|(_, u)| **u) {
+        let c = crate::color::ansi_256(i as u8);
+        let percent = |v: u8| (v as u32 * 100 + 127) / 255;
+        buf.push_str(&format!("#{};2;{};{};{}", i, percent(c.0), percent(c.1), percent(c.2)));
+    }
+
+    for band in 0..raster.height.div_ceil(6) {
+        if band > 0 {
+            buf.push('-');
+        }
+        let rows = (band * 6)..((band + 1) * 6).min(raster.height);
+        let mut in_band = [false; 256];
+        for y in rows.clone() {
+            for i in &indices[y * raster.width..(y + 1) * raster.width] {
+                in_band[*i as usize] = true;
+            }
+        }
+        let mut first = true;
+        for (color, _) in in_band.iter().enumerate().filter(|(_, u)| **u) {
+            if !first {
+                buf.push('$');
+            }
+            first = false;
+            buf.push_str(&format!("#{}", color));
+
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..raster.width {
+                let mut bits = 0;
+                for (bit, y) in rows.clone().enumerate() {
+                    if indices[y * raster.width + x] as usize == color {
+                        bits |= 1 << bit;
+                    }
+                }
+                let ch = (63 + bits) as u8 as char;
+                run = match run {
+                    Some((c, n)) if c == ch => Some((c, n + 1)),
+                    Some(previous) => {
+                        push_run(&mut buf, previous);
+                        Some((ch, 1))
+                    }
+                    None => Some((ch, 1)),
+                };
+            }
+            if let Some(last) = run {
+                push_run(&mut buf, last);
+            }
+        }
+    }
+    buf.push_str("\x1B\\");
+    out.write_all(buf.as_bytes())
+}
+
+fn push_run(buf: &mut String, (ch, n): (char, usize)) {
+    if n > 3 {
+        buf.push_str(&format!("!{}{}", n, ch));
+    } else {
+        buf.extend(std::iter::repeat_n(ch, n));
+    }
+}
+
+// Sends raw RGB data. Sending again with the same id replaces the previous
+// image, so animations don't pile up images in the terminal.
+pub fn kitty<W: Write>(raster: &Raster, id: u32, out: &mut W) -> io::Result<()> {
+    const CHUNK: usize = 4096;
+    let mut data = Vec::with_capacity(raster.pixels.len() * 3);
+    for p in &raster.pixels {
+        data.extend_from_slice(&[p.0, p.1, p.2]);
+    }
+    let encoded = base64(&data);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(CHUNK).collect();
+    let count = chunks.len().max(1);
+    for i in 0..count {
+        let more = (i + 1 < count) as u8;
+        if i == 0 {
+            write!(
+                out,
+                "\x1B_Ga=T,f=24,s={},v={},i={},q=2,m={};",
+                raster.width, raster.height, id, more
+            )?;
+        } else {
+            write!(out, "\x1B_Gm={};", more)?;
+        }
+        out.write_all(chunks.get(i).copied().unwrap_or_default())?;
+        out.write_all(b"\x1B\\")?;
+    }
+    Ok(())
+}
+
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{raster::Raster, RGB};
+
+    use super::{base64, kitty, sixel};
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+    }
+
+    #[test]
+    fn sixel_golden() {
+        let raster = Raster {
+            width: 5,
+            height: 2,
+            pixels: vec![
+                RGB(0, 0, 255),
+                RGB(255, 0, 0),
+                RGB(255, 0, 0),
+                RGB(255, 0, 0),
+                RGB(255, 0, 0),
+                RGB(0, 0, 255),
+                RGB(0, 0, 255),
+                RGB(255, 0, 0),
+                RGB(255, 0, 0),
+                RGB(255, 0, 0),
+            ],
+        };
+        let mut out = Vec::new();
+        sixel(&raster, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1BPq\"1;1;5;2#21;2;0;0;100#196;2;100;0;0#21BA???$#196?@BBB\x1B\\"
+        );
+    }
+
+    #[test]
+    fn kitty_golden() {
+        let mut out = Vec::new();
+        kitty(&Raster::new(1, 1, RGB(255, 0, 0)), 7, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1B_Ga=T,f=24,s=1,v=1,i=7,q=2,m=0;/wAA\x1B\\"
+        );
+    }
+}
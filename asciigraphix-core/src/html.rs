@@ -0,0 +1,190 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{record::json_escape, Cell, RGB};
+
+const FOREGROUND: RGB = RGB(229, 229, 229);
+const BACKGROUND: RGB = RGB(0, 0, 0);
+
+// The cells as HTML, one line per row, with consecutive cells of the same
+// style in a single inline-styled span. Goes inside a `<pre>`.
+pub fn spans(cells: &[Cell], width: usize) -> String {
+    let mut out = String::new();
+    for (y, row) in cells.chunks(width.max(1)).enumerate() {
+        if y > 0 {
+            out.push('\n');
+        }
+        let mut run: Option<(String, String)> = None;
+        for cell in row {
+            let style = style(cell);
+            match &mut run {
+                Some((s, text)) if *s == style => push_escaped(text, cell.glyph),
+                _ => {
+                    if let Some(previous) = run.take() {
+                        push_span(&mut out, previous);
+                    }
+                    let mut text = String::new();
+                    push_escaped(&mut text, cell.glyph);
+                    run = Some((style, text));
+                }
+            }
+        }
+        if let Some(last) = run {
+            push_span(&mut out, last);
+        }
+    }
+    out
+}
+
+// A self-contained `<pre>` block of the cells, for pasting into a page.
+pub fn pre(cells: &[Cell], width: usize) -> String {
+    format!("<pre style=\"{}\">{}</pre>", pre_style(), spans(cells, width))
+}
+
+// A page that plays the frames back in a loop, each shown until the time of
+// the next one. A single frame is just shown.
+pub fn page(frames: &[(Duration, String)]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>asciigraphix</title>\n</head>\n",
+    );
+    let _ = writeln!(out, "<body style=\"margin:0;background:{}\">", hex(BACKGROUND));
+    let first = frames.first().map(|(_, f)| f.as_str()).unwrap_or_default();
+    let _ = writeln!(out, "<pre id=\"frame\" style=\"{}\">{}</pre>", pre_style(), first);
+    if frames.len() > 1 {
+        out.push_str("<script>\nconst frames = [\n");
+        for (at, frame) in frames {
+            // keeps "</script>" inside a frame from ending the script
+            let text = json_escape(frame).replace("</", "<\\/");
+            let _ = writeln!(out, "[{:.3}, \"{}\"],", at.as_secs_f64(), text);
+        }
+        out.push_str(
+            "];
+const pre = document.getElementById(\"frame\");
+let i = 0;
+function show() {
+    pre.innerHTML = frames[i][1];
+    const next = (i + 1) % frames.length;
+    const delay = next == 0 ? 0.1 : frames[next][0] - frames[i][0];
+    i = next;
+    setTimeout(show, delay * 1000);
+}
+show();
+</script>
+",
+        );
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// Collects frames and writes them as a playback page when finished.
+pub struct HtmlRecorder<W: Write> {
+    out: W,
+    frames: Vec<(Duration, String)>,
+}
+
+impl<W: Write> HtmlRecorder<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            frames: Vec::new(),
+        }
+    }
+
+    // `at` is the time since the start of the recording.
+    pub fn frame(&mut self, cells: &[Cell], columns: usize, at: Duration) {
+        self.frames.push((at, spans(cells, columns)));
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(page(&self.frames).as_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn pre_style() -> String {
+    format!(
+        "margin:0;font-family:monospace;line-height:1.2;color:{};background:{}",
+        hex(FOREGROUND),
+        hex(BACKGROUND)
+    )
+}
+
+// Blanks only show their background, so they join spans of any foreground.
+fn style(cell: &Cell) -> String {
+    let mut style = String::new();
+    if cell.glyph != ' ' {
+        if let Some(fg) = cell.fg {
+            let _ = write!(style, "color:{};", hex(fg));
+        }
+        if cell.bold {
+            style.push_str("font-weight:bold;");
+        }
+    }
+    if let Some(bg) = cell.bg {
+        let _ = write!(style, "background:{};", hex(bg));
+    }
+    style
+}
+
+fn push_span(out: &mut String, (style, text): (String, String)) {
+    if style.is_empty() {
+        out.push_str(&text);
+    } else {
+        let _ = write!(out, "<span style=\"{}\">{}</span>", style, text);
+    }
+}
+
+fn push_escaped(out: &mut String, ch: char) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        c => out.push(c),
+    }
+}
+
+fn hex(c: RGB) -> String {
+    format!("#{:06x}", c.to_u32())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Cell, RGB};
+
+    use super::{page, spans};
+
+    #[test]
+    fn merged_spans() {
+        let red = Cell {
+            glyph: '<',
+            fg: Some(RGB(255, 0, 0)),
+            ..Cell::EMPTY
+        };
+        let cells = [red, red, Cell::EMPTY, Cell { bold: true, ..red }];
+        assert_eq!(
+            spans(&cells, 2),
+            "<span style=\"color:#ff0000;\">&lt;&lt;</span>\n \
+             <span style=\"color:#ff0000;font-weight:bold;\">&lt;</span>"
+        );
+    }
+
+    #[test]
+    fn playback() {
+        let one = page(&[(Duration::ZERO, "a".into())]);
+        assert!(one.contains(">a</pre>"));
+        assert!(!one.contains("<script>"));
+        let many = page(&[
+            (Duration::ZERO, "</script>".into()),
+            (Duration::from_millis(500), "b".into()),
+        ]);
+        assert!(many.contains("[0.000, \"<\\/script>\"],\n[0.500, \"b\"],"));
+    }
+}
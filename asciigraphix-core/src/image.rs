@@ -0,0 +1,236 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::raster::Raster;
+
+impl Raster {
+    // Binary PPM (P6), the simplest format image viewers understand.
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let mut data = Vec::with_capacity(self.pixels.len() * 3);
+        for p in &self.pixels {
+            data.extend_from_slice(&[p.0, p.1, p.2]);
+        }
+        out.write_all(&data)
+    }
+
+    pub fn write_png<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1A\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bit depth, truecolor, default compression, filter and no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(out, b"IHDR", &header)?;
+
+        // every row starts with its filter type, 0 is none
+        let mut data = Vec::with_capacity(self.height * (1 + self.width * 3));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            data.push(0);
+            for p in row {
+                data.extend_from_slice(&[p.0, p.1, p.2]);
+            }
+        }
+        write_chunk(out, b"IDAT", &zlib(&data, 1 + self.width * 3))?;
+        write_chunk(out, b"IEND", &[])
+    }
+
+    // Format picked from the extension, .png or .ppm.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let mut out = BufWriter::new(File::create(path)?);
+        match extension.as_deref() {
+            Some("png") => self.write_png(&mut out)?,
+            Some("ppm") => self.write_ppm(&mut out)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "unsupported image format, use .png or .ppm",
+                ));
+            }
+        }
+        out.flush()
+    }
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+pub(crate) fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+// Writes bits least significant first, as deflate wants them.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u32, count: u32) {
+        self.bits |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes go most significant bit first.
+    fn push_code(&mut self, code: u32, count: u32) {
+        self.push(code.reverse_bits() >> (32 - count), count);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+fn push_symbol(out: &mut BitWriter, symbol: u16) {
+    match symbol {
+        0..=143 => out.push_code(0x30 + symbol as u32, 8),
+        144..=255 => out.push_code(0x190 + (symbol as u32 - 144), 9),
+        256..=279 => out.push_code(symbol as u32 - 256, 7),
+        _ => out.push_code(0xC0 + (symbol as u32 - 280), 8),
+    }
+}
+
+fn push_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let l = LENGTH_BASE.iter().rposition(|b| *b as usize <= length).unwrap_or(0);
+    push_symbol(out, 257 + l as u16);
+    out.push((length - LENGTH_BASE[l] as usize) as u32, LENGTH_EXTRA[l] as u32);
+    let d = DISTANCE_BASE.iter().rposition(|b| *b as usize <= distance).unwrap_or(0);
+    out.push_code(d as u32, 5);
+    out.push((distance - DISTANCE_BASE[d] as usize) as u32, DISTANCE_EXTRA[d] as u32);
+}
+
+// A zlib stream using a single fixed Huffman block. Matches are only looked for
+// one pixel back and one row up, which is all it takes for mostly flat
+// framebuffers to shrink to a fraction of their size.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    const MAX_LENGTH: usize = 258;
+    const MAX_DISTANCE: usize = 32768;
+    let mut out = BitWriter {
+        bytes: vec![0x78, 0x01],
+        bits: 0,
+        count: 0,
+    };
+    // final block, fixed codes
+    out.push(0b011, 3);
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        for distance in [3, stride, 1] {
+            if distance == 0 || distance > i || distance > MAX_DISTANCE {
+                continue;
+            }
+            let length = (0..MAX_LENGTH.min(data.len() - i))
+                .take_while(|k| data[i + k] == data[i + k - distance])
+                .count();
+            if length > best.0 {
+                best = (length, distance);
+            }
+        }
+        if best.0 >= 3 {
+            push_match(&mut out, best.0, best.1);
+            i += best.0;
+        } else {
+            push_symbol(&mut out, data[i] as u16);
+            i += 1;
+        }
+    }
+    push_symbol(&mut out, 256);
+
+    let mut bytes = out.finish();
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{raster::Raster, RGB};
+
+    use super::crc32;
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        Raster::new(2, 1, RGB(1, 2, 3)).write_ppm(&mut out).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\x01\x02\x03\x01\x02\x03");
+    }
+
+    #[test]
+    fn png_is_compressed() {
+        let mut raster = Raster::new(64, 64, RGB(10, 10, 10));
+        raster.fill_rect(8, 8, 16, 16, RGB(0, 205, 205));
+        let mut out = Vec::new();
+        raster.write_png(&mut out).unwrap();
+        assert!(out.starts_with(b"\x89PNG\r\n\x1A\n"));
+        assert!(out.ends_with(b"IEND\xAE\x42\x60\x82"));
+        assert!(out.len() < 64 * 64 * 3 / 10);
+    }
+}
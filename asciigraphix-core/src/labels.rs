@@ -0,0 +1,137 @@
+use crate::{
+    shapes::{Point, Shape},
+    Cell, Display, RGB,
+};
+
+// What the debug overlay marks, for checking hand built shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugOverlay {
+    // a marker and the index on every vertex
+    pub vertices: bool,
+    // the index at the middle of every edge
+    pub edges: bool,
+    pub color: RGB,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            vertices: true,
+            edges: false,
+            color: RGB(255, 215, 0),
+        }
+    }
+}
+
+impl Display {
+    const MARKER: char = '●';
+
+    // Shows indices of the shape's vertices and edges on top of the frame.
+    pub fn set_debug_overlay(&mut self, overlay: Option<DebugOverlay>) {
+        self.debug = overlay;
+    }
+
+    // Writes text next to where a point ends up on screen, to the right if it
+    // fits and else to the left. Labels don't overwrite each other, so a label
+    // that doesn't fit anywhere is dropped and `false` returned.
+    //
+    // Only lasts until the next frame is drawn.
+    pub fn label(&mut self, at: &Point, text: &str, color: RGB) -> bool {
+        let Some((i, _, _)) = self.locate(at) else {
+            return false;
+        };
+        let (x, y) = (i % self.x_size, i / self.x_size);
+        let length = text.chars().count();
+        let right = (x + 1 + length <= self.x_size).then_some(x + 1);
+        let left = x.checked_sub(length);
+        let free = |start: usize| !self.overlay[y * self.x_size + start..][..length].contains(&true);
+        let Some(start) = right.filter(|s| free(*s)).or(left.filter(|s| free(*s))) else {
+            return false;
+        };
+        for (k, glyph) in text.chars().enumerate() {
+            let i = y * self.x_size + start + k;
+            self.cells[i] = Cell {
+                glyph,
+                fg: Some(color),
+                ..Cell::EMPTY
+            };
+            self.overlay[i] = true;
+        }
+        true
+    }
+
+    // Cells covered by labels and markers in the last frame, the rest comes
+    // from the depth buffer.
+    pub fn overlay(&self) -> &[bool] {
+        &self.overlay
+    }
+
+    pub(crate) fn draw_debug_overlay(&mut self, shape: &Shape) {
+        self.overlay.fill(false);
+        let Some(debug) = self.debug else {
+            return;
+        };
+        if debug.vertices {
+            // markers first, so labels go around all of them
+            for vertex in &shape.vertices {
+                if let Some((i, _, _)) = self.locate(vertex) {
+                    self.cells[i] = Cell {
+                        glyph: Self::MARKER,
+                        fg: Some(debug.color),
+                        bold: true,
+                        ..Cell::EMPTY
+                    };
+                    self.overlay[i] = true;
+                }
+            }
+            for (v, vertex) in shape.vertices.iter().enumerate() {
+                self.label(vertex, &v.to_string(), debug.color);
+            }
+        }
+        if debug.edges {
+            let color = RGB(debug.color.0 / 2, debug.color.1 / 2, debug.color.2 / 2);
+            for (e, edge) in shape.edges.iter().enumerate() {
+                let (a, b) = (shape.vertices[edge.0], shape.vertices[edge.1]);
+                let middle = a + (b - a) * 0.5;
+                self.label(&middle, &format!("e{}", e), color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shapes::{Point, Shape},
+        Display, RGB,
+    };
+
+    use super::DebugOverlay;
+
+    #[test]
+    fn vertex_labels() {
+        let line = Shape::generate_line(Point(-5.0, 0.0, 0.0), Point(5.0, 0.0, 0.0));
+        let mut display = Display::new(40, 20, Point(0.0, -20.0, 0.0), Point::e(2), 20.0);
+        display.set_debug_overlay(Some(DebugOverlay {
+            edges: true,
+            ..DebugOverlay::default()
+        }));
+        display.draw(&line);
+        let row: String = display.cells()[10 * 40..11 * 40].iter().map(|c| c.glyph).collect();
+        assert_eq!(row.trim(), "●0****e0**●1");
+        assert_eq!(display.overlay().iter().filter(|o| **o).count(), 6);
+    }
+
+    #[test]
+    fn labels_dont_overlap() {
+        let mut display = Display::new(10, 3, Point(0.0, -20.0, 0.0), Point::e(2), 20.0);
+        display.draw(&Shape::generate_line(Point::zero(), Point::zero()));
+        let at = Point(0.0, 0.0, 0.0);
+        assert!(display.label(&at, "abc", RGB(1, 1, 1)));
+        // goes to the left instead
+        assert!(display.label(&at, "de", RGB(1, 1, 1)));
+        assert!(!display.label(&at, "f", RGB(1, 1, 1)));
+        let row: String = display.cells()[20..].iter().map(|c| c.glyph).collect();
+        assert_eq!(row, "   de*abc ");
+    }
+}
@@ -1,18 +1,86 @@
-use shapes::{Edge, Point};
+use std::{
+    io::{self, Write},
+    path::Path,
+};
 
+use ansi::AnsiEncoder;
+use color::{ColorDepth, Dither};
+use cue::{DepthCue, WGradient};
+use cull::RenderStats;
+use graphics::GraphicsProtocol;
+use labels::DebugOverlay;
+use raster::Raster;
+use shapes::{EdgeStyle, Face, LineStyle, Point};
+
+pub mod ansi;
+pub mod bounds;
+pub mod color;
+pub mod cull;
+pub mod cue;
+pub mod font;
+pub mod graphics;
+pub mod html;
+pub mod image;
+pub mod labels;
+pub mod mesh;
+pub mod parametric;
+pub mod plot;
+pub mod primitives;
+pub mod raster;
+pub mod record;
+pub mod scene;
 pub mod shapes;
+pub mod svg;
+pub mod unfold;
 
 pub struct Display {
     x_size: usize,
     y_size: usize,
-    pixels: Vec<Vec<Option<f64>>>,
+    // depth buffer of points and edges, row-major
+    pixels: Vec<Option<Fragment>>,
+    // nearest face per pixel with its id, for hidden lines and solid fill.
+    // Ids are indices into the shape's faces, offset per instance.
+    face_depth: Vec<Option<(f64, usize)>>,
+    // how many faces cover each pixel, for translucent faces
+    face_layers: Vec<u8>,
+    hidden_lines: HiddenLines,
+    glyph_style: GlyphStyle,
+    depth_cue: DepthCue,
+    w_gradient: Option<WGradient>,
+    // the w mapped to the ends of the gradient in the last frame
+    w_range: f64,
+    back_face_culling: bool,
+    solid: bool,
+    translucent: bool,
+    outline: Option<Outline>,
+    // direction of the outline through each pixel, if there is one
+    outline_mask: Vec<Option<(f64, f64)>>,
+    // (depth, color) per pixel, reused between frames by `render`
+    frame: Vec<(f32, u32)>,
+    // scenes flattened into one shape, reused between frames
+    scene: shapes::Shape,
+    // the vertices of one instance at a time
+    instance: Vec<Point>,
+    // which instances are in view
+    in_view: Vec<bool>,
+    // highest priority of the edges at each vertex of the shape being drawn
+    priorities: Vec<u8>,
+    stats: RenderStats,
+    // glyphs and colors produced from the depth buffer
+    cells: Vec<Cell>,
+    debug: Option<DebugOverlay>,
+    // cells written by `label` and the debug overlay
+    overlay: Vec<bool>,
+    color: RGB,
+    ansi: AnsiEncoder,
+    raster: Raster,
     pub cam_pos: Point,
     cam_unit_vectors: (Point, Point, Point),
     cam_focal: f64,
 }
 
-// 8-bit color
-pub struct RGB(u8, u8, u8);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RGB(pub u8, pub u8, pub u8);
 
 impl RGB {
     pub fn to_u32(&self) -> u32 {
@@ -20,27 +88,37 @@ impl RGB {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::RGB;
+// A single character of output. `None` colors use the terminal default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub glyph: char,
+    pub fg: Option<RGB>,
+    pub bg: Option<RGB>,
+    pub bold: bool,
+}
 
-    #[test]
-    fn rgb() {
-        let color = RGB(255, 5, 15);
-        assert_eq!(color.to_u32(), 0x00FF050F);
-    }
+impl Cell {
+    pub const EMPTY: Cell = Cell {
+        glyph: ' ',
+        fg: None,
+        bg: None,
+        bold: false,
+    };
 }
 
-#[derive(Copy, Clone)]
-#[allow(dead_code)]
-enum TextColor {
-    Red,
-    Cyan,
-    BrightCyan,
-    BrightGreen,
+impl Default for Cell {
+    fn default() -> Self {
+        Self::EMPTY
+    }
 }
 
 impl Display {
+    const CYAN: RGB = RGB(0, 205, 205);
+    const BACKGROUND: RGB = RGB(0, 0, 0);
+    // Depths this close, relatively, count as the same surface. Lines lie on
+    // their faces, but faces are only sampled at pixel centers.
+    const DEPTH_TOLERANCE: f64 = 0.02;
+
     pub fn new(
         x_size: usize,
         y_size: usize,
@@ -48,158 +126,1012 @@ impl Display {
         cam_direction: Point,
         cam_focal: f64,
     ) -> Display {
-        let pixels: Vec<Vec<Option<f64>>> = vec![vec![None; x_size]; y_size];
         Display {
             x_size,
             y_size,
-            pixels,
+            pixels: vec![None; x_size * y_size],
+            face_depth: vec![None; x_size * y_size],
+            face_layers: vec![0; x_size * y_size],
+            hidden_lines: HiddenLines::Show,
+            glyph_style: GlyphStyle::Depth,
+            depth_cue: DepthCue::default(),
+            w_gradient: None,
+            w_range: 0.0,
+            back_face_culling: false,
+            solid: false,
+            translucent: false,
+            outline: None,
+            outline_mask: vec![None; x_size * y_size],
+            frame: Vec::with_capacity(x_size * y_size),
+            scene: shapes::Shape::empty(),
+            instance: Vec::new(),
+            in_view: Vec::new(),
+            priorities: Vec::new(),
+            stats: RenderStats::default(),
+            cells: vec![Cell::EMPTY; x_size * y_size],
+            debug: None,
+            overlay: vec![false; x_size * y_size],
+            color: Self::CYAN,
+            ansi: AnsiEncoder::incremental(),
+            raster: Raster::new(0, 0, Self::BACKGROUND),
             cam_pos,
             cam_unit_vectors: Self::orthogonal_basis(cam_direction),
             cam_focal,
         }
     }
 
+    pub fn size(&self) -> (usize, usize) {
+        (self.x_size, self.y_size)
+    }
+
+    // Buffers are only reallocated when the size actually changes.
+    pub fn resize(&mut self, x_size: usize, y_size: usize) {
+        if (x_size, y_size) == (self.x_size, self.y_size) {
+            return;
+        }
+        self.x_size = x_size;
+        self.y_size = y_size;
+        self.pixels.clear();
+        self.pixels.resize(x_size * y_size, None);
+        self.face_depth.clear();
+        self.face_depth.resize(x_size * y_size, None);
+        self.face_layers.clear();
+        self.face_layers.resize(x_size * y_size, 0);
+        self.outline_mask.clear();
+        self.outline_mask.resize(x_size * y_size, None);
+        self.frame.clear();
+        self.frame.reserve(x_size * y_size);
+        self.cells.clear();
+        self.cells.resize(x_size * y_size, Cell::EMPTY);
+        self.overlay.clear();
+        self.overlay.resize(x_size * y_size, false);
+    }
+
+    // Colors used by `render_print`, true color by default. Pass
+    // `ColorDepth::detect()` for the terminal this process runs in.
+    pub fn set_color_depth(&mut self, depth: ColorDepth, dither: Dither) {
+        self.ansi.set_color_depth(depth, dither);
+    }
+
+    pub fn set_color(&mut self, color: RGB) {
+        self.color = color;
+    }
+
+    // Edges behind faces of the shape are shown, hidden or dimmed and dashed.
+    pub fn set_hidden_lines(&mut self, mode: HiddenLines) {
+        self.hidden_lines = mode;
+    }
+
+    pub fn set_glyph_style(&mut self, style: GlyphStyle) {
+        self.glyph_style = style;
+    }
+
+    // Fading with distance, for the depth glyphs and all colors.
+    pub fn set_depth_cue(&mut self, cue: DepthCue) {
+        self.depth_cue = cue;
+    }
+
+    pub fn depth_cue(&self) -> &DepthCue {
+        &self.depth_cue
+    }
+
+    // Colors shapes projected from 4D by their w, interpolated along edges.
+    // Shapes without w keep the plain color.
+    pub fn set_w_gradient(&mut self, gradient: Option<WGradient>) {
+        self.w_gradient = gradient;
+    }
+
+    // Faces are filled in, instead of only drawing the wireframe.
+    pub fn set_solid(&mut self, solid: bool) {
+        self.solid = solid;
+    }
+
+    // Faces are filled in but everything behind them still shows, with more
+    // layers of faces drawn denser. Solid faces take precedence.
+    pub fn set_translucent(&mut self, translucent: bool) {
+        self.translucent = translucent;
+    }
+
+    // Outlines where depth jumps or surfaces fold, drawn over whatever ended
+    // up in the frame.
+    pub fn set_outline(&mut self, outline: Option<Outline>) {
+        self.outline = outline;
+    }
+
+    // Skips faces pointing away from the camera. Faces must be wound
+    // counter-clockwise seen from outside.
+    pub fn set_back_face_culling(&mut self, culling: bool) {
+        self.back_face_culling = culling;
+    }
+
+    // The glyphs of the last frame drawn, row by row.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    // What the last `render` returned.
+    pub fn frame(&self) -> &[(f32, u32)] {
+        &self.frame
+    }
+
+    // The depth buffer of the last frame drawn, row by row.
+    pub fn fragments(&self) -> &[Option<Fragment>] {
+        &self.pixels
+    }
+
+    pub fn set_camera(&mut self, cam_pos: Point, cam_direction: Point) {
+        self.cam_pos = cam_pos;
+        self.cam_unit_vectors = Self::orthogonal_basis(cam_direction);
+    }
+
     fn orthogonal_basis(cam_direction: Point) -> (Point, Point, Point) {
-        // Pick a vector from xy-plane i.e. (x, y, 0) that is orthogonal to cam_direction
-        let a = Point(cam_direction.1, -cam_direction.0, 0.0).unit();
+        // Pick a vector from xy-plane i.e. (x, y, 0) that is orthogonal to cam_direction,
+        // any will do when looking along z
+        let a = Point(cam_direction.1, -cam_direction.0, 0.0).try_unit().unwrap_or(Point::e(1));
         let b = cam_direction.cross(&a).unit();
         (cam_direction.unit(), a, b)
     }
 
-    fn clear_screen() {
-        // clear screen
-        print!("\x1B[2J\x1B[1;1H");
-        // hide cursor
-        print!("\x1B[?25l");
+    // A point relative to the camera: (right, down, depth).
+    fn view(&self, point: &Point) -> (f64, f64, f64) {
+        let cam_to_point = *point - self.cam_pos;
+        (
+            cam_to_point.dot(&self.cam_unit_vectors.1),
+            cam_to_point.dot(&self.cam_unit_vectors.2),
+            cam_to_point.dot(&self.cam_unit_vectors.0),
+        )
+    }
+
+    // Where a point in view space lands on the screen, unrounded. Only makes
+    // sense for points in front of the camera.
+    fn screen(&self, (x, y, depth): (f64, f64, f64)) -> (f64, f64) {
+        (
+            self.cam_focal * x / depth + self.x_size as f64 / 2.0,
+            self.cam_focal * y / depth + self.y_size as f64 / 2.0,
+        )
     }
 
-    fn project_point(&mut self, point: &Point) {
-        let cam_to_point = point.clone() - self.cam_pos.clone();
-        // dbg!(self.cam_unit_vectors.clone());
-        let depth = cam_to_point.dot(&self.cam_unit_vectors.0);
-        let x = cam_to_point.dot(&self.cam_unit_vectors.1);
-        let y = cam_to_point.dot(&self.cam_unit_vectors.2);
+    // The cell a point lands in, its depth and unrounded screen position, if
+    // it is on screen.
+    fn locate(&self, point: &Point) -> Option<(usize, f64, (f64, f64))> {
+        let view = self.view(point);
+        let depth = view.2;
         if depth <= 0.0 {
-            return;
+            return None;
         }
-        // let x = self.cam_unit_vectors.clone().1 * dot_x;
-        // let y = self.cam_unit_vectors.clone().2 * dot_y;
-        // let z = self.cam_unit_vectors.clone().0 * dot_z;
-
-        let x_pixel = self.cam_focal * x / depth;
-        let y_pixel = self.cam_focal * y / depth;
-        let x_pixel = x_pixel + (self.x_size as f64 / 2.0);
-        let y_pixel = y_pixel + (self.y_size as f64 / 2.0);
+        let (x_pixel, y_pixel) = self.screen(view);
         if (x_pixel < 0.0) || (y_pixel < 0.0) {
-            return;
+            return None;
         }
+        let position = (x_pixel, y_pixel);
         let x_pixel = x_pixel.round() as usize;
         let y_pixel = y_pixel.round() as usize;
         if (x_pixel >= self.x_size) || (y_pixel >= self.y_size) {
-            return;
+            return None;
         }
-        let cur_depth = self.pixels[y_pixel][x_pixel];
-        if let Some(k) = cur_depth {
-            if depth > k {
-                self.pixels[y_pixel][x_pixel] = Some(depth);
+        Some((y_pixel * self.x_size + x_pixel, depth, position))
+    }
+
+    // Higher priority edges win, then the nearest fragment, except that
+    // vertices win over the ends of their edges.
+    fn plot(&mut self, i: usize, fragment: Fragment) {
+        let pixel = &mut self.pixels[i];
+        match pixel {
+            Some(k) if k.style.priority != fragment.style.priority => {
+                if fragment.style.priority > k.style.priority {
+                    *pixel = Some(fragment)
+                }
+            }
+            Some(k) if fragment.kind == FragmentKind::Vertex
+                && k.kind == FragmentKind::Edge
+                && fragment.depth <= k.depth * (1.0 + Self::DEPTH_TOLERANCE) =>
+            {
+                *pixel = Some(fragment)
+            }
+            Some(k) if k.depth <= fragment.depth => {}
+            _ => *pixel = Some(fragment),
+        }
+    }
+
+    // Whether a face other than the ones `touches` accepts is in front of
+    // `depth` at pixel `i`. `faces` are the ones with ids from `first`.
+    fn occluded(
+        &self,
+        i: usize,
+        depth: f64,
+        (faces, first): (&[Face], usize),
+        touches: impl Fn(&Face) -> bool,
+    ) -> bool {
+        match self.face_depth[i] {
+            Some((d, id)) => {
+                let own = id.checked_sub(first).and_then(|f| faces.get(f)).is_some_and(touches);
+                d < depth * (1.0 - Self::DEPTH_TOLERANCE) && !own
+            }
+            None => false,
+        }
+    }
+
+    // Vertices are drawn over their edges, so they take the highest priority
+    // of them. Done once per shape, however many instances there are.
+    fn find_priorities(&mut self, shape: &shapes::Shape) {
+        self.priorities.clear();
+        self.priorities.resize(shape.vertices.len(), 0);
+        for (e, edge) in shape.edges.iter().enumerate() {
+            for v in [edge.0, edge.1] {
+                self.priorities[v] = self.priorities[v].max(shape.edge_style(e).priority);
             }
-        } else {
-            self.pixels[y_pixel][x_pixel] = Some(depth);
         }
     }
 
-    fn project_vertices(&mut self, vertices: &Vec<Point>) {
-        for vertex in vertices {
-            self.project_point(vertex);
+    // Projects the shape's vertices as given in `vertices`, which is where an
+    // instance has them. `first_face` is the id of the instance's first face
+    // and `color` overrides the shape's colors. Needs `find_priorities` first.
+    fn project_vertices(&mut self, shape: &shapes::Shape, vertices: &[Point], first_face: usize, color: Option<RGB>) {
+        let faces = (&shape.faces[..], first_face);
+        self.stats.vertices += vertices.len();
+        for (v, vertex) in vertices.iter().enumerate() {
+            if let Some((i, depth, position)) = self.locate(vertex) {
+                let fragment = Fragment {
+                    depth,
+                    kind: FragmentKind::Vertex,
+                    hidden: self.occluded(i, depth, faces, |f| [f.0, f.1, f.2].contains(&v)),
+                    position,
+                    direction: (0.0, 0.0),
+                    style: EdgeStyle {
+                        priority: self.priorities[v],
+                        ..EdgeStyle::default()
+                    },
+                    w: shape.w.get(v).copied(),
+                    color: color.or(shape.colors.get(v).copied().flatten()),
+                };
+                self.plot_line(i, fragment, true);
+            }
         }
     }
 
-    fn project_edges(&mut self, vertices: &Vec<Point>, edges: &Vec<Edge>) {
-        for edge in edges {
-            let start = vertices[edge.0].clone();
-            let end = vertices[edge.1].clone();
+    fn project_edges(&mut self, shape: &shapes::Shape, vertices: &[Point], first_face: usize, color: Option<RGB>) {
+        let (w, faces) = (&shape.w, (&shape.faces[..], first_face));
+        self.stats.edges += shape.edges.len();
+        // screen distance of a dash and of the gap after it, for hidden and
+        // dashed lines, and of a dot and the gap after it
+        const DASH: f64 = 2.0;
+        const DOT: f64 = 1.0;
+        for (e, edge) in shape.edges.iter().enumerate() {
+            let edge_style = shape.edge_style(e);
+            let start = vertices[edge.0];
+            let end = vertices[edge.1];
 
-            let delta = end.clone() - start.clone();
-            // let delta_unit = delta.clone() / delta.magnitude();
+            let delta = end - start;
+            let length = match (self.locate(&start), self.locate(&end)) {
+                (Some((_, _, a)), Some((_, _, b))) => ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt(),
+                _ => 100.0,
+            };
+            let touches = |f: &Face| {
+                let face = [f.0, f.1, f.2];
+                face.contains(&edge.0) && face.contains(&edge.1)
+            };
 
             const VERTEX_DENSITY: usize = 100;
+            self.stats.samples += VERTEX_DENSITY;
             for c in 0..VERTEX_DENSITY {
-                let point = start.clone() + delta.clone() * (c as f64 / VERTEX_DENSITY as f64);
-                self.project_point(&point);
+                let t = c as f64 / VERTEX_DENSITY as f64;
+                let point = start + delta * t;
+                if let Some((i, depth, position)) = self.locate(&point) {
+                    // lines stay straight in perspective, but where they go on
+                    // screen changes when part of the edge is behind the camera
+                    let ahead = self.screen(self.view(&(point + delta * 1e-3)));
+                    let fragment = Fragment {
+                        depth,
+                        kind: FragmentKind::Edge,
+                        hidden: self.occluded(i, depth, faces, touches),
+                        position,
+                        direction: (ahead.0 - position.0, ahead.1 - position.1),
+                        style: edge_style,
+                        w: w.get(edge.0).zip(w.get(edge.1)).map(|(a, b)| a + (b - a) * t),
+                        color: color.or(shape.colors.get(edge.0).copied().flatten()),
+                    };
+                    let dash = ((t * length / DASH) as usize).is_multiple_of(2);
+                    let gap = match edge_style.line {
+                        LineStyle::Dashed => !dash,
+                        // centered on cells, so every other one gets a dot
+                        LineStyle::Dotted => !((t * length / DOT + 0.5) as usize).is_multiple_of(2),
+                        _ => false,
+                    };
+                    if !gap {
+                        self.plot_line(i, fragment, dash);
+                    }
+                }
             }
         }
     }
 
-    // fn project_faces(&mut self, vertices: &Vec<Point>, faces: &Vec<Face>) {
-    //     for face in faces {
-    //         let vertex_1 = vertices.get(face.0);
-    //         let vertex_2 = vertices.get(face.1);
-    //         let vertex_3 = vertices.get(face.2);
-    //     }
-    // }
+    // Plots part of a line according to the hidden line mode.
+    fn plot_line(&mut self, i: usize, mut fragment: Fragment, dash: bool) {
+        fragment.hidden &= self.hidden_lines != HiddenLines::Show;
+        if fragment.hidden && (self.hidden_lines == HiddenLines::Hide || !dash) {
+            return;
+        }
+        self.plot(i, fragment);
+    }
+
+    // Screen space triangle of a face, `None` when it isn't completely in
+    // front of the camera or is culled.
+    pub(crate) fn triangle(&self, vertices: &[Point], face: &Face) -> Option<Triangle> {
+        let indices = [face.0, face.1, face.2];
+        let [a, b, c] = indices.map(|i| vertices[i]);
+        if self.back_face_culling && (b - a).cross(&(c - a)).dot(&(self.cam_pos - a)) <= 0.0 {
+            return None;
+        }
+        let view = [a, b, c].map(|p| self.view(&p));
+        if view.iter().any(|v| v.2 <= 0.0) {
+            return None;
+        }
+        Some(Triangle {
+            vertices: indices,
+            points: view.map(|v| {
+                let (x, y) = self.screen(v);
+                (x, y, v.2)
+            }),
+        })
+    }
+
+    fn project_faces(&mut self, vertices: &[Point], faces: &[Face], first_face: usize) {
+        self.stats.faces += faces.len();
+        for (f, face) in faces.iter().enumerate() {
+            let Some(triangle) = self.triangle(vertices, face) else {
+                continue;
+            };
+            let (min, max) = triangle.points.iter().fold(
+                ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)),
+                |(min, max), p| ((min.0.min(p.0), min.1.min(p.1)), (max.0.max(p.0), max.1.max(p.1))),
+            );
+            if max.0 < 0.0 || max.1 < 0.0 {
+                continue;
+            }
+            let x_range = (min.0.max(0.0).ceil() as usize)..=(max.0.floor() as usize).min(self.x_size.saturating_sub(1));
+            let y_range = (min.1.max(0.0).ceil() as usize)..=(max.1.floor() as usize).min(self.y_size.saturating_sub(1));
+            for y in y_range {
+                for x in x_range.clone() {
+                    let Some(depth) = triangle.depth_at((x as f64, y as f64)) else {
+                        continue;
+                    };
+                    let i = y * self.x_size + x;
+                    self.face_layers[i] = self.face_layers[i].saturating_add(1);
+                    let pixel = &mut self.face_depth[i];
+                    match pixel {
+                        Some((k, _)) if *k <= depth => {}
+                        _ => *pixel = Some((depth, first_face + f)),
+                    }
+                }
+            }
+        }
+    }
+
+    // Whether faces have to be projected at all.
+    fn needs_faces(&self) -> bool {
+        self.solid || self.translucent || self.hidden_lines != HiddenLines::Show || self.outline.is_some()
+    }
+
+    fn clear(&mut self, shape: &shapes::Shape) {
+        self.pixels.fill(None);
+        self.face_depth.fill(None);
+        self.face_layers.fill(0);
+        self.stats = RenderStats::default();
+        self.w_range = match self.w_gradient {
+            Some(WGradient { range: Some(range), .. }) => range,
+            _ => shape.w.iter().fold(0.0, |max, w| w.abs().max(max)),
+        };
+    }
 
     fn project(&mut self, shape: &shapes::Shape) {
-        self.pixels = vec![vec![None; self.x_size]; self.y_size];
-        self.project_vertices(&shape.vertices);
-        self.project_edges(&shape.vertices, &shape.edges);
-        // self.project_faces(&shape.vertices, &shape.faces);
+        self.clear(shape);
+        self.stats.objects = 1;
+        if self.needs_faces() {
+            self.project_faces(&shape.vertices, &shape.faces, 0);
+        }
+        self.project_edges(shape, &shape.vertices, 0, None);
+        self.find_priorities(shape);
+        self.project_vertices(shape, &shape.vertices, 0, None);
+        self.fill_faces(|f| shape.colors.get(shape.faces[f].0).copied().flatten());
+        if let Some(outline) = self.outline {
+            self.detect_outlines(outline);
+        }
     }
 
-    fn colored(text: &str, color: TextColor) {
-        match color {
-            TextColor::Red => print!("\x1b[31m{}\x1b[0m", text),
-            TextColor::Cyan => print!("\x1b[36m{}\x1b[0m", text),
-            TextColor::BrightCyan => print!("\x1b[96m{}\x1b[0m", text),
-            TextColor::BrightGreen => print!("\x1b[92m{}\x1b[0m", text),
+    // Covers what is behind faces when solid, and shows faces where there is
+    // nothing else when translucent. `color` is the color of a face by id.
+    fn fill_faces(&mut self, color: impl Fn(usize) -> Option<RGB>) {
+        if !self.solid && !self.translucent {
+            return;
+        }
+        for (i, (pixel, face)) in self.pixels.iter_mut().zip(&self.face_depth).enumerate() {
+            let Some((depth, f)) = face else {
+                continue;
+            };
+            // lines on or in front of the face stay, and ones with a
+            // priority, the rest is covered unless it shows through
+            let covered = |f: &Fragment| {
+                self.solid && f.style.priority == 0 && f.depth > depth * (1.0 + Self::DEPTH_TOLERANCE)
+            };
+            if pixel.is_none_or(|f| covered(&f)) {
+                *pixel = Some(Fragment {
+                    depth: *depth,
+                    kind: FragmentKind::Face,
+                    hidden: false,
+                    position: ((i % self.x_size) as f64, (i / self.x_size) as f64),
+                    direction: (0.0, 0.0),
+                    style: EdgeStyle::default(),
+                    w: None,
+                    color: color(*f),
+                });
+            }
+        }
+    }
+
+    // Post-pass over the depth buffer only, so it works the same for
+    // wireframes, filled faces and point clouds.
+    fn detect_outlines(&mut self, outline: Outline) {
+        let (w, h) = (self.x_size as isize, self.y_size as isize);
+        // only faces have surfaces to outline, lines and points would all
+        // look like thin silhouettes
+        let depth = |x: isize, y: isize| -> Option<f64> {
+            if x < 0 || y < 0 || x >= w || y >= h {
+                return None;
+            }
+            self.face_depth[(y * w + x) as usize].map(|(depth, _)| depth)
+        };
+        // back from the screen into view space
+        let point = |x: isize, y: isize| {
+            depth(x, y).map(|d| {
+                Point(
+                    (x as f64 - w as f64 / 2.0) * d / self.cam_focal,
+                    (y as f64 - h as f64 / 2.0) * d / self.cam_focal,
+                    d,
+                )
+            })
+        };
+        let normal = |x: isize, y: isize| {
+            let p = point(x, y)?;
+            let n = (point(x + 1, y)? - p).cross(&(point(x, y + 1)? - p));
+            (n.magnitude() > 0.0).then(|| n.unit())
+        };
+        let crease = outline.crease_angle.to_radians().cos();
+
+        for y in 0..h {
+            for x in 0..w {
+                let i = (y * w + x) as usize;
+                self.outline_mask[i] = None;
+                let Some(d) = depth(x, y) else {
+                    continue;
+                };
+                let empty = |nx: isize, ny: isize| depth(nx, ny).is_none() as i32 as f64;
+                let gradient = (empty(x + 1, y) - empty(x - 1, y), empty(x, y + 1) - empty(x, y - 1));
+                // how much nearer this pixel is than its neighbours on both
+                // sides, zero for any flat surface however steep
+                let bulge = |a: Option<f64>, b: Option<f64>| match (a, b) {
+                    (Some(a), Some(b)) => (a + b) / 2.0 - d,
+                    _ => 0.0,
+                };
+                let bulge_x = bulge(depth(x - 1, y), depth(x + 1, y));
+                let bulge_y = bulge(depth(x, y - 1), depth(x, y + 1));
+                if gradient != (0.0, 0.0) {
+                    self.outline_mask[i] = Some((-gradient.1, gradient.0));
+                } else if empty(x + 1, y) + empty(x - 1, y) + empty(x, y + 1) + empty(x, y - 1) > 0.0 {
+                    // thin, nothing on opposite sides
+                    let direction = if empty(x + 1, y) > 0.0 { (0.0, 1.0) } else { (1.0, 0.0) };
+                    self.outline_mask[i] = Some(direction);
+                } else if bulge_x.max(bulge_y) > outline.depth_jump * d {
+                    // only the near side of a jump gets the line
+                    let direction = if bulge_x >= bulge_y { (0.0, 1.0) } else { (1.0, 0.0) };
+                    self.outline_mask[i] = Some(direction);
+                } else if let Some(n) = normal(x, y) {
+                    // the line between differently facing neighbours is
+                    // across from them
+                    if normal(x + 1, y).is_some_and(|m| n.dot(&m) < crease) {
+                        self.outline_mask[i] = Some((0.0, 1.0));
+                    } else if normal(x, y + 1).is_some_and(|m| n.dot(&m) < crease) {
+                        self.outline_mask[i] = Some((1.0, 0.0));
+                    }
+                }
+            }
+        }
+
+        for (pixel, mask) in self.pixels.iter_mut().zip(&self.outline_mask) {
+            if let (Some(fragment), Some(direction)) = (pixel, mask) {
+                fragment.kind = FragmentKind::Outline;
+                fragment.direction = *direction;
+            }
         }
     }
 
-    // Vec<(depth, color)>
+    fn shade(&mut self) {
+        let cue = &self.depth_cue;
+        for ((cell, z), layers) in self.cells.iter_mut().zip(&self.pixels).zip(&self.face_layers) {
+            *cell = match z {
+                Some(fragment) => {
+                    let color = match (self.w_gradient, fragment.w) {
+                        (Some(gradient), Some(w)) => gradient.color(w, self.w_range),
+                        _ => fragment.color.unwrap_or(self.color),
+                    };
+                    let color = if fragment.hidden || fragment.kind == FragmentKind::Face {
+                        dim(color)
+                    } else {
+                        color
+                    };
+                    let glyph = match fragment.kind {
+                        FragmentKind::Face if self.translucent && !self.solid => match layers {
+                            0..=1 => '░',
+                            2 => '▒',
+                            _ => '▓',
+                        },
+                        _ => glyph(self.glyph_style, cue, fragment),
+                    };
+                    Cell {
+                        glyph,
+                        bold: fragment.style.line == LineStyle::Thick || cue.bold(fragment.depth),
+                        fg: Some(cue.apply(color, fragment.depth)),
+                        ..Cell::EMPTY
+                    }
+                }
+                None => Cell::EMPTY,
+            };
+        }
+    }
+
+    // [(depth, color)] row by row
     // for now color is set to some default
-    pub fn render(&mut self, shape: &shapes::Shape) -> Vec<(f32, u32)> {
+    // the glyphs are in `cells` as well
+    pub fn render(&mut self, shape: &shapes::Shape) -> &[(f32, u32)] {
+        self.project(shape);
+        self.shade();
+        self.draw_debug_overlay(shape);
+        self.fill_frame();
+        &self.frame
+    }
+
+    fn fill_frame(&mut self) {
         const FG: RGB = RGB(254,0,0);
         const BG: RGB = RGB(10,10,10);
-        self.project(&shape);
-        let mut result = Vec::new();
-        for row in &self.pixels {
-            for z in row {
-                match z {
-                    Some(p) => {
-                        result.push(((*p as f32).clone(), FG.to_u32()));
-                    }
-                    None => {
-                        result.push((0.0 as f32, BG.to_u32()));
-                    }
+        self.frame.clear();
+        for z in &self.pixels {
+            match z {
+                Some(f) if f.hidden || f.kind == FragmentKind::Face => {
+                    let color = self.depth_cue.apply(dim(FG), f.depth);
+                    self.frame.push((f.depth as f32, color.to_u32()));
+                }
+                Some(f) => {
+                    let color = self.depth_cue.apply(FG, f.depth);
+                    self.frame.push((f.depth as f32, color.to_u32()));
                 }
+                None => {
+                    self.frame.push((0.0, BG.to_u32()));
+                }
+            }
+        }
+    }
+
+    // Draws the shape into the framebuffer without output anywhere, see
+    // `cells` and `snapshot`.
+    pub fn draw(&mut self, shape: &shapes::Shape) {
+        self.project(shape);
+        self.shade();
+        self.draw_debug_overlay(shape);
+    }
+
+    // An image of the last frame drawn.
+    pub fn snapshot(&self, style: ImageStyle) -> Raster {
+        let mut raster = Raster::new(0, 0, Self::BACKGROUND);
+        match style {
+            ImageStyle::Pixels => {
+                raster.draw_cells(&self.cells, self.x_size, (1, 1), Self::BACKGROUND)
             }
+            ImageStyle::Text => raster.draw_text(&self.cells, self.x_size, Self::BACKGROUND),
         }
-        result
+        raster
     }
 
+    // Saves the last frame drawn as a .png or .ppm file.
+    pub fn save_image<P: AsRef<Path>>(&self, path: P, style: ImageStyle) -> io::Result<()> {
+        self.snapshot(style).save(path)
+    }
+
+    // Saves the last frame drawn as an HTML page with colored text.
+    pub fn save_html<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let frame = html::spans(&self.cells, self.x_size);
+        std::fs::write(path, html::page(&[(std::time::Duration::ZERO, frame)]))
+    }
+
+    pub fn render_ansi<W: Write>(
+        &mut self,
+        shape: &shapes::Shape,
+        encoder: &mut AnsiEncoder,
+        out: &mut W,
+    ) -> io::Result<()> {
+        self.draw(shape);
+        encoder.encode(&self.cells, self.x_size, out)
+    }
+
+    // Draws the frame as an image at the top left of the terminal, each cell
+    // becoming `scale` pixels. For a crisp image make the display as large as
+    // the image should be and use a scale of (1, 1).
+    pub fn render_graphics<W: Write>(
+        &mut self,
+        shape: &shapes::Shape,
+        protocol: GraphicsProtocol,
+        scale: (usize, usize),
+        out: &mut W,
+    ) -> io::Result<()> {
+        self.draw(shape);
+        self.raster
+            .draw_cells(&self.cells, self.x_size, scale, Self::BACKGROUND);
+        out.write_all(b"\x1B[H")?;
+        protocol.encode(&self.raster, out)
+    }
+
+    // Draws to stdout, only updating what changed since the previous call.
     pub fn render_print(&mut self, shape: &shapes::Shape) {
-        self.project(&shape);
-        Self::clear_screen();
-        let color = TextColor::Cyan;
-        for row in &self.pixels {
-            for z in row {
-                match z {
-                    Some(p) => {
-                        if *p < 10.0 {
-                            Self::colored("#", color);
-                        } else if *p < 30.0 {
-                            Self::colored("*", color);
-                        } else if *p < 50.0 {
-                            Self::colored("-", color);
-                        } else {
-                            Self::colored(".", color);
-                        }
-                    }
-                    None => {
-                        print!(" ");
-                    }
-                }
+        self.draw(shape);
+        self.print();
+    }
+
+    // Writes the last frame drawn to stdout.
+    pub fn print(&mut self) {
+        let mut stdout = io::stdout().lock();
+        self.ansi
+            .encode(&self.cells, self.x_size, &mut stdout)
+            .and_then(|_| stdout.flush())
+            .expect("failed writing to stdout");
+    }
+}
+
+// How a frame is turned into an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageStyle {
+    // one pixel per cell
+    Pixels,
+    // glyphs drawn with a bitmap font, like a screenshot of the terminal
+    Text,
+}
+
+// What ended up in a pixel of the depth buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment {
+    pub depth: f64,
+    pub kind: FragmentKind,
+    // behind a face, only kept when hidden lines are dashed
+    pub hidden: bool,
+    // where on screen, unrounded
+    pub position: (f64, f64),
+    // screen direction of the edge, zero for vertices and faces
+    pub direction: (f64, f64),
+    // of the edge, vertices only get the priority of their edges
+    pub style: EdgeStyle,
+    // 4th coordinate, for shapes projected from 4D
+    pub w: Option<f64>,
+    // of the shape at this point, if it has one
+    pub color: Option<RGB>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentKind {
+    Vertex,
+    Edge,
+    Face,
+    // found by the outline pass, over any of the others
+    Outline,
+}
+
+// When the outline pass draws a line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    // depth change between neighbouring pixels beyond what a flat surface
+    // gives, relative to the depth
+    pub depth_jump: f64,
+    // degrees between the surfaces on both sides
+    pub crease_angle: f64,
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self {
+            depth_jump: 0.1,
+            crease_angle: 40.0,
+        }
+    }
+}
+
+// A face projected to the screen, screen coordinates and depth per corner.
+pub(crate) struct Triangle {
+    pub(crate) vertices: [usize; 3],
+    pub(crate) points: [(f64, f64, f64); 3],
+}
+
+impl Triangle {
+    // Depth of the triangle at a screen position, if it covers it.
+    pub(crate) fn depth_at(&self, (x, y): (f64, f64)) -> Option<f64> {
+        let [a, b, c] = self.points;
+        let area = (b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1);
+        if area.abs() < 1e-12 {
+            return None;
+        }
+        let wa = ((b.0 - x) * (c.1 - y) - (c.0 - x) * (b.1 - y)) / area;
+        let wb = ((c.0 - x) * (a.1 - y) - (a.0 - x) * (c.1 - y)) / area;
+        let wc = 1.0 - wa - wb;
+        // a little slack, so points right on an edge shared by two triangles
+        // don't fall through both
+        const SLACK: f64 = -1e-9;
+        if wa < SLACK || wb < SLACK || wc < SLACK {
+            return None;
+        }
+        // 1/depth is what is linear on screen
+        Some(1.0 / (wa / a.2 + wb / b.2 + wc / c.2))
+    }
+}
+
+// How fragments are turned into characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphStyle {
+    // denser glyphs closer to the camera
+    Depth,
+    // - | / \ _ following the edges on screen, + for vertices
+    Slope,
+}
+
+fn glyph(style: GlyphStyle, cue: &DepthCue, fragment: &Fragment) -> char {
+    let line = match fragment.kind {
+        FragmentKind::Edge if !fragment.hidden => fragment.style.line,
+        _ => LineStyle::Solid,
+    };
+    match (style, fragment.kind) {
+        (_, FragmentKind::Face) => '░',
+        _ if line == LineStyle::Dotted => '·',
+        _ if line == LineStyle::Double => match slope_glyph(fragment) {
+            '-' | '_' => '═',
+            '|' => '║',
+            c => c,
+        },
+        (GlyphStyle::Depth, FragmentKind::Outline) => '#',
+        (GlyphStyle::Depth, _) if fragment.hidden => '.',
+        (GlyphStyle::Depth, _) => cue.glyph(fragment.depth),
+        (GlyphStyle::Slope, FragmentKind::Vertex) if fragment.hidden => 'o',
+        (GlyphStyle::Slope, FragmentKind::Vertex) => '+',
+        (GlyphStyle::Slope, FragmentKind::Edge | FragmentKind::Outline) => slope_glyph(fragment),
+    }
+}
+
+// - _ | / \ following the direction of a line on screen.
+fn slope_glyph(fragment: &Fragment) -> char {
+    // cells are about twice as tall as they are wide
+    let (dx, dy) = (fragment.direction.0, fragment.direction.1 * 2.0);
+    let angle = dy.atan2(dx).to_degrees().rem_euclid(180.0);
+    match angle {
+        a if !(22.5..157.5).contains(&a) => {
+            // low in its cell, y goes down
+            if fragment.position.1 - fragment.position.1.round() > 0.25 {
+                '_'
+            } else {
+                '-'
             }
-            println!()
         }
+        a if a < 67.5 => '\\',
+        a if a < 112.5 => '|',
+        _ => '/',
+    }
+}
+
+fn dim(color: RGB) -> RGB {
+    RGB(color.0 / 2, color.1 / 2, color.2 / 2)
+}
+
+// What happens to the parts of lines that are behind faces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenLines {
+    Show,
+    Hide,
+    Dashed,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cue::WGradient,
+        shapes::{Edge, EdgeStyle, LineStyle, Point, Point4, Shape, Shape4},
+        Display, GlyphStyle, HiddenLines, Outline, RGB,
+    };
+
+    #[test]
+    fn rgb() {
+        let color = RGB(255, 5, 15);
+        assert_eq!(color.to_u32(), 0x00FF050F);
+    }
+
+    #[test]
+    fn resize() {
+        let cube = Shape::generate_cube(Point::zero(), 10.0);
+        let mut display = Display::new(0, 0, Point(0.0, -50.0, 0.0), Point::e(2), 20.0);
+        assert!(display.render(&cube).is_empty());
+        display.resize(40, 20);
+        let frame = display.render(&cube);
+        assert_eq!(frame.len(), 40 * 20);
+        assert!(frame.iter().any(|(depth, _)| *depth > 0.0));
+    }
+
+    #[test]
+    fn looking_along_z() {
+        // there used to be no sideways direction to pick
+        let cube = Shape::generate_cube(Point::zero(), 10.0);
+        let mut display = Display::new(40, 20, Point(0.0, 0.0, -50.0), Point::e(3), 20.0);
+        display.draw(&cube);
+        assert!(display.cells().iter().filter(|c| c.glyph != ' ').count() > 8);
+    }
+
+    #[test]
+    fn hidden_lines() {
+        let mut cube = Shape::generate_cube(Point::zero(), 14.0);
+        cube.rotate(&Point::zero(), (0.5, 0.6, 0.0));
+        let mut display = Display::new(70, 34, Point(0.0, -40.0, 0.0), Point::e(2), 40.0);
+        let count = |display: &mut Display, glyph: char| {
+            display.draw(&cube);
+            display.cells().iter().filter(|c| c.glyph == glyph).count()
+        };
+        let shown = count(&mut display, '-');
+        display.set_hidden_lines(HiddenLines::Hide);
+        let visible = count(&mut display, '-');
+        assert!(visible < shown);
+        display.set_hidden_lines(HiddenLines::Dashed);
+        assert!(count(&mut display, '.') > 0);
+        display.set_solid(true);
+        assert!(count(&mut display, '░') > 0);
+    }
+
+    #[test]
+    fn back_face_culling() {
+        let cube = Shape::generate_cube(Point::zero(), 10.0);
+        // counter-clockwise seen from outside
+        for face in &cube.faces {
+            let [a, b, c] = [face.0, face.1, face.2].map(|v| cube.vertices[v]);
+            assert!((b - a).cross(&(c - a)).dot(&(a + b + c)) > 0.0);
+        }
+        let kept = |display: &Display| cube.faces.iter().filter(|f| display.triangle(&cube.vertices, f).is_some()).count();
+        let mut display = Display::new(40, 20, Point(0.0, -50.0, 0.0), Point::e(2), 20.0);
+        assert_eq!(kept(&display), 12);
+        display.set_back_face_culling(true);
+        // only the front
+        assert_eq!(kept(&display), 2);
+        // front, right and top from a corner
+        display.set_camera(Point(30.0, -30.0, 30.0), Point(-1.0, 1.0, -1.0));
+        assert_eq!(kept(&display), 6);
+    }
+
+    #[test]
+    fn translucent_faces() {
+        let mut cube = Shape::generate_cube(Point::zero(), 14.0);
+        cube.rotate(&Point::zero(), (0.5, 0.6, 0.0));
+        let mut display = Display::new(70, 34, Point(0.0, -40.0, 0.0), Point::e(2), 40.0);
+        let lines = |display: &Display| display.cells().iter().filter(|c| "#*-.".contains(c.glyph)).count();
+        display.draw(&cube);
+        let wireframe = lines(&display);
+        display.set_translucent(true);
+        display.draw(&cube);
+        // everything behind the faces still shows
+        assert_eq!(lines(&display), wireframe);
+        // front and back of the cube
+        assert!(display.cells().iter().any(|c| c.glyph == '▒'));
+        display.set_solid(true);
+        display.draw(&cube);
+        assert!(lines(&display) < wireframe);
+    }
+
+    #[test]
+    fn slope_glyphs() {
+        let lines = Shape {
+            vertices: vec![
+                Point(-5.0, 0.0, 0.0),
+                Point(5.0, 0.0, 0.0),
+                Point(0.0, 0.0, 2.0),
+                Point(0.0, 0.0, 8.0),
+            ],
+            edges: vec![Edge::new(0, 1), Edge::new(2, 3)],
+            faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
+            center: None,
+        };
+        let mut display = Display::new(40, 20, Point(0.0, -20.0, 0.0), Point::e(2), 20.0);
+        display.set_glyph_style(GlyphStyle::Slope);
+        display.draw(&lines);
+        let row = |y: usize| -> String { display.cells()[y * 40..(y + 1) * 40].iter().map(|c| c.glyph).collect() };
+        assert_eq!(row(10).trim(), "+---------+");
+        assert_eq!(row(7).trim(), "|");
+    }
+
+    #[test]
+    fn edge_styles() {
+        let lines = Shape {
+            vertices: vec![
+                Point(-5.0, 0.0, 0.0),
+                Point(5.0, 0.0, 0.0),
+                Point(0.0, 5.0, -4.0),
+                Point(0.0, 5.0, 4.0),
+                Point(-8.0, 0.0, -4.0),
+                Point(-8.0, 0.0, 4.0),
+            ],
+            edges: vec![Edge::new(0, 1), Edge::new(2, 3), Edge::new(4, 5)],
+            faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: vec![
+                EdgeStyle::new(LineStyle::Double),
+                // further away, but drawn over the double line
+                EdgeStyle::new(LineStyle::Thick).with_priority(1),
+                EdgeStyle::new(LineStyle::Dotted),
+            ],
+            center: None,
+        };
+        let mut display = Display::new(40, 20, Point(0.0, -20.0, 0.0), Point::e(2), 20.0);
+        display.set_glyph_style(GlyphStyle::Slope);
+        display.draw(&lines);
+        let row = |y: usize| -> String { display.cells()[y * 40..(y + 1) * 40].iter().map(|c| c.glyph).collect() };
+        assert_eq!(row(10).trim(), "·  +════|════+");
+        assert!(display.cells()[10 * 40 + 20].bold);
+        assert_eq!(row(9).trim(), "|");
+        assert_eq!(row(8).trim(), "·       |");
+    }
+
+    #[test]
+    fn outlines() {
+        let mut cube = Shape::generate_cube(Point::zero(), 14.0);
+        cube.rotate(&Point::zero(), (0.5, 0.6, 0.0));
+        cube.edges.clear();
+        let mut display = Display::new(60, 26, Point(0.0, -40.0, 0.0), Point::e(2), 40.0);
+        display.set_solid(true);
+        let count = |display: &mut Display, outline: Outline| {
+            display.set_outline(Some(outline));
+            display.draw(&cube);
+            display.cells().iter().filter(|c| c.glyph == '#').count()
+        };
+        let silhouette = count(&mut display, Outline { crease_angle: 180.0, ..Outline::default() });
+        let creases = count(&mut display, Outline::default());
+        assert!(silhouette > 0);
+        assert!(creases > silhouette);
+
+        // a wireframe keeps its lines
+        let mut wireframe = Shape::generate_cube(Point::zero(), 14.0);
+        wireframe.rotate(&Point::zero(), (0.5, 0.6, 0.0));
+        wireframe.faces.clear();
+        let mut display = Display::new(60, 26, Point(0.0, -40.0, 0.0), Point::e(2), 40.0);
+        display.set_glyph_style(GlyphStyle::Slope);
+        display.draw(&wireframe);
+        let lines: Vec<char> = display.cells().iter().map(|c| c.glyph).collect();
+        display.set_outline(Some(Outline::default()));
+        display.draw(&wireframe);
+        assert!(display.cells().iter().map(|c| c.glyph).eq(lines));
+    }
+
+    #[test]
+    fn w_colors() {
+        let line = Shape4 {
+            vertices: vec![Point4(-5.0, 0.0, 0.0, -2.0), Point4(5.0, 0.0, 0.0, 2.0)],
+            edges: vec![Edge::new(0, 1)],
+            edge_styles: Vec::new(),
+            faces: Vec::new(),
+            facets: Vec::new(),
+            center: None,
+        }
+        .project_to_3d();
+        assert_eq!(line.w, [-2.0, 2.0]);
+        let mut display = Display::new(40, 20, Point(0.0, -20.0, 0.0), Point::e(2), 20.0);
+        let gradient = WGradient::default();
+        display.set_w_gradient(Some(gradient));
+        display.draw(&line);
+        let row = &display.cells()[10 * 40..11 * 40];
+        assert_eq!(row[15].fg, Some(gradient.negative));
+        assert_eq!(row[25].fg, Some(gradient.positive));
+        let middle = row[20].fg.unwrap();
+        assert!(gradient.negative.0 < middle.0 && middle.0 < gradient.positive.0);
+        // plain 3D shapes aren't colored
+        display.draw(&Shape::generate_line(Point(-5.0, 0.0, 0.0), Point(5.0, 0.0, 0.0)));
+        assert_ne!(display.cells()[10 * 40 + 15].fg, Some(gradient.negative));
     }
 }
@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::shapes::{Edge, EdgeStyle, Face, Point, Shape};
+
+// The first thing wrong with a shape found by `Shape::validate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeshError {
+    // a coordinate that is NaN or infinite
+    NonFinite { vertex: usize },
+    DanglingEdge { edge: usize, vertex: usize },
+    DanglingFace { face: usize, vertex: usize },
+    // an edge from a vertex to itself
+    DegenerateEdge { edge: usize },
+    // the same two vertices as an earlier edge, either way around
+    DuplicateEdge { edge: usize, first: usize },
+    // a repeated corner or corners on one line
+    DegenerateFace { face: usize },
+}
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeshError::NonFinite { vertex } => write!(f, "vertex {} is not finite", vertex),
+            MeshError::DanglingEdge { edge, vertex } => {
+                write!(f, "edge {} refers to vertex {}, which doesn't exist", edge, vertex)
+            }
+            MeshError::DanglingFace { face, vertex } => {
+                write!(f, "face {} refers to vertex {}, which doesn't exist", face, vertex)
+            }
+            MeshError::DegenerateEdge { edge } => write!(f, "edge {} starts and ends at the same vertex", edge),
+            MeshError::DuplicateEdge { edge, first } => write!(f, "edge {} is the same as edge {}", edge, first),
+            MeshError::DegenerateFace { face } => write!(f, "face {} has no area", face),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+// The vertices of an edge in either direction.
+fn key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl Shape {
+    // Checks everything `Display` relies on, so a bad shape fails here
+    // instead of panicking halfway through drawing it.
+    pub fn validate(&self) -> Result<(), MeshError> {
+        let count = self.vertices.len();
+        if let Some(vertex) = self.vertices.iter().position(|v| !(v.0.is_finite() && v.1.is_finite() && v.2.is_finite())) {
+            return Err(MeshError::NonFinite { vertex });
+        }
+        let mut seen = HashMap::new();
+        for (e, edge) in self.edges.iter().enumerate() {
+            if let Some(vertex) = [edge.0, edge.1].into_iter().find(|v| *v >= count) {
+                return Err(MeshError::DanglingEdge { edge: e, vertex });
+            }
+            if edge.0 == edge.1 {
+                return Err(MeshError::DegenerateEdge { edge: e });
+            }
+            if let Some(first) = seen.insert(key(edge.0, edge.1), e) {
+                return Err(MeshError::DuplicateEdge { edge: e, first });
+            }
+        }
+        for (f, face) in self.faces.iter().enumerate() {
+            let corners = [face.0, face.1, face.2];
+            if let Some(vertex) = corners.into_iter().find(|v| *v >= count) {
+                return Err(MeshError::DanglingFace { face: f, vertex });
+            }
+            let [a, b, c] = corners.map(|v| self.vertices[v]);
+            let (ab, ac) = (b - a, c - a);
+            // zero when two corners are the same as well
+            if ab.cross(&ac).magnitude() <= 1e-12 * ab.magnitude() * ac.magnitude() {
+                return Err(MeshError::DegenerateFace { face: f });
+            }
+        }
+        Ok(())
+    }
+
+    // Merges vertices closer than `tolerance` to each other into the first
+    // of them, then drops the edges and faces that collapse or repeat.
+    // Returns how many vertices went.
+    pub fn weld_vertices(&mut self, tolerance: f64) -> usize {
+        // vertices by the cube of side `tolerance` they are in, so only
+        // neighbouring cubes need checking
+        let cell = |p: &Point| {
+            if tolerance > 0.0 {
+                [p.0, p.1, p.2].map(|c| (c / tolerance).floor() as i64)
+            } else {
+                [p.0, p.1, p.2].map(|c| c.to_bits() as i64)
+            }
+        };
+        let reach = if tolerance > 0.0 { 1 } else { 0 };
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut kept: Vec<usize> = Vec::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        for (v, vertex) in self.vertices.iter().enumerate() {
+            let [x, y, z] = cell(vertex);
+            let mut same = None;
+            'search: for dx in -reach..=reach {
+                for dy in -reach..=reach {
+                    for dz in -reach..=reach {
+                        let Some(candidates) = cells.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+                        if let Some(k) = candidates.iter().find(|k| (self.vertices[kept[**k]] - *vertex).magnitude() <= tolerance) {
+                            same = Some(*k);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+            remap.push(same.unwrap_or_else(|| {
+                cells.entry([x, y, z]).or_default().push(kept.len());
+                kept.push(v);
+                kept.len() - 1
+            }));
+        }
+
+        let removed = self.vertices.len() - kept.len();
+        self.vertices = kept.iter().map(|v| self.vertices[*v]).collect();
+        if !self.w.is_empty() {
+            self.w = kept.iter().map(|v| self.w.get(*v).copied().unwrap_or(0.0)).collect();
+        }
+        if !self.colors.is_empty() {
+            self.colors = kept.iter().map(|v| self.colors.get(*v).copied().flatten()).collect();
+        }
+        let mut seen = HashSet::new();
+        let (edges, edge_styles): (Vec<Edge>, Vec<EdgeStyle>) = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(k, e)| (Edge(remap[e.0], remap[e.1]), self.edge_style(k)))
+            .filter(|(e, _)| e.0 != e.1 && seen.insert(key(e.0, e.1)))
+            .unzip();
+        self.edges = edges;
+        if !self.edge_styles.is_empty() {
+            self.edge_styles = edge_styles;
+        }
+        self.faces = self
+            .faces
+            .iter()
+            .map(|f| Face(remap[f.0], remap[f.1], remap[f.2]))
+            .filter(|f| f.0 != f.1 && f.1 != f.2 && f.0 != f.2)
+            .collect();
+        removed
+    }
+
+    // Adds an edge along every side of the faces that doesn't have one yet,
+    // for shapes made of faces only. Returns how many were added.
+    pub fn edges_from_faces(&mut self) -> usize {
+        let mut seen: HashSet<_> = self.edges.iter().map(|e| key(e.0, e.1)).collect();
+        let before = self.edges.len();
+        for face in &self.faces {
+            for (a, b) in [(face.0, face.1), (face.1, face.2), (face.2, face.0)] {
+                if a != b && seen.insert(key(a, b)) {
+                    self.edges.push(Edge::new(a, b));
+                }
+            }
+        }
+        self.edges.len() - before
+    }
+
+    // The vertices each vertex shares an edge with, in order. Faces without
+    // edges don't count, see `edges_from_faces`.
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![Vec::new(); self.vertices.len()];
+        for edge in &self.edges {
+            if edge.0 != edge.1 {
+                neighbours[edge.0].push(edge.1);
+                neighbours[edge.1].push(edge.0);
+            }
+        }
+        for list in &mut neighbours {
+            list.sort_unstable();
+            list.dedup();
+        }
+        neighbours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::{Edge, Face, Point, Shape};
+
+    use super::MeshError;
+
+    #[test]
+    fn validate() {
+        let center = Point(1.0, 2.0, 3.0);
+        for shape in [
+            Shape::generate_cube(center, 2.0),
+            Shape::generate_ring(5.0, center),
+            Shape::generate_grid(4),
+            Shape::generate_parallelepiped(center, Point::e(1), Point::e(2), Point::e(3)),
+        ] {
+            assert_eq!(shape.validate(), Ok(()));
+        }
+
+        let mut cube = Shape::generate_cube(Point::zero(), 2.0);
+        cube.edges.push(Edge::new(0, 8));
+        assert_eq!(cube.validate(), Err(MeshError::DanglingEdge { edge: 12, vertex: 8 }));
+        cube.edges[12] = Edge::new(cube.edges[3].1, cube.edges[3].0);
+        assert_eq!(cube.validate(), Err(MeshError::DuplicateEdge { edge: 12, first: 3 }));
+        cube.edges.pop();
+        cube.faces.push(Face(0, 1, 1));
+        assert!(matches!(cube.validate(), Err(MeshError::DegenerateFace { .. })));
+        cube.vertices[2].1 = f64::NAN;
+        assert_eq!(cube.validate(), Err(MeshError::NonFinite { vertex: 2 }));
+        assert!(Point::zero().try_unit().is_none());
+    }
+
+    #[test]
+    fn weld() {
+        // two triangles with their own copies of the shared side
+        let mut shape = Shape::empty();
+        shape.vertices = vec![
+            Point(0.0, 0.0, 0.0),
+            Point(1.0, 0.0, 0.0),
+            Point(0.0, 1.0, 0.0),
+            Point(1.0, 0.0, 1e-9),
+            Point(0.0, 1.0, 0.0),
+            Point(1.0, 1.0, 0.0),
+        ];
+        shape.faces = vec![Face(0, 1, 2), Face(3, 5, 4)];
+        assert_eq!(shape.edges_from_faces(), 6);
+        assert_eq!(shape.weld_vertices(1e-6), 2);
+        assert_eq!(shape.vertices.len(), 4);
+        assert_eq!(shape.edges.len(), 5);
+        assert_eq!(shape.faces[1], Face(1, 3, 2));
+        assert_eq!(shape.validate(), Ok(()));
+        assert_eq!(shape.adjacency(), [vec![1, 2], vec![0, 2, 3], vec![0, 1, 3], vec![1, 2]]);
+    }
+}
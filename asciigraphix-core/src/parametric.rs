@@ -0,0 +1,245 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use crate::shapes::{Edge, Face, Point, Point4, Shape, Shape4};
+
+// Points closer than this, relative to the size of the shape, are the same.
+const SEAM_TOLERANCE: f64 = 1e-9;
+
+// `samples` values from the start to the end of `range`, both included.
+fn steps(range: &RangeInclusive<f64>, samples: usize) -> impl Iterator<Item = f64> + '_ {
+    let samples = samples.max(2);
+    (0..samples).map(move |k| range.start() + (range.end() - range.start()) * k as f64 / (samples - 1) as f64)
+}
+
+// Edges and faces of a grid of `rows` by `columns` vertices, row by row.
+// Faces go counter-clockwise from the side the cross product of the row and
+// column directions points to.
+fn grid(rows: usize, columns: usize) -> (Vec<Edge>, Vec<Face>) {
+    let at = |row: usize, column: usize| row * columns + column;
+    let mut edges = Vec::new();
+    let mut faces = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            if row + 1 < rows {
+                edges.push(Edge::new(at(row, column), at(row + 1, column)));
+            }
+            if column + 1 < columns {
+                edges.push(Edge::new(at(row, column), at(row, column + 1)));
+            }
+            if row + 1 < rows && column + 1 < columns {
+                let [a, b, c, d] = [at(row, column), at(row + 1, column), at(row + 1, column + 1), at(row, column + 1)];
+                faces.push(Face(a, b, c));
+                faces.push(Face(a, c, d));
+            }
+        }
+    }
+    (edges, faces)
+}
+
+// The vertices on the outside of a grid, where closed curves and surfaces
+// meet themselves. Only the ends of a curve, its middle may cross itself.
+fn border(rows: usize, columns: usize) -> Vec<usize> {
+    if rows == 1 || columns == 1 {
+        return vec![0, rows * columns - 1];
+    }
+    (0..rows * columns)
+        .filter(|v| {
+            let (row, column) = (v / columns, v % columns);
+            row == 0 || row + 1 == rows || column == 0 || column + 1 == columns
+        })
+        .collect()
+}
+
+// Joins the vertices on the border that `same` says are one, like the ends
+// of a closed curve, the sides of a tube, a twisted seam or a pole where a
+// whole row ends up in one place. Drops edges and faces that collapse or
+// repeat, and returns the vertices that are left.
+fn stitch(
+    count: usize,
+    border: &[usize],
+    same: impl Fn(usize, usize) -> bool,
+    edges: &mut Vec<Edge>,
+    faces: &mut Vec<Face>,
+) -> Vec<usize> {
+    let mut remap: Vec<usize> = (0..count).collect();
+    for (k, v) in border.iter().enumerate() {
+        if let Some(first) = border[..k].iter().find(|b| remap[**b] == **b && same(**b, *v)) {
+            remap[*v] = *first;
+        }
+    }
+    let kept: Vec<usize> = (0..count).filter(|v| remap[*v] == *v).collect();
+    let mut index = vec![0; count];
+    for (new, old) in kept.iter().enumerate() {
+        index[*old] = new;
+    }
+    let moved = |v: usize| index[remap[v]];
+    let mut seen = HashSet::new();
+    edges.retain_mut(|e| {
+        *e = Edge(moved(e.0), moved(e.1));
+        e.0 != e.1 && seen.insert((e.0.min(e.1), e.0.max(e.1)))
+    });
+    faces.retain_mut(|f| {
+        *f = Face(moved(f.0), moved(f.1), moved(f.2));
+        f.0 != f.1 && f.1 != f.2 && f.0 != f.2
+    });
+    kept
+}
+
+// How close points have to be to be stitched, for coordinates up to `size`.
+fn tolerance(size: f64) -> f64 {
+    SEAM_TOLERANCE * size.max(1.0)
+}
+
+// The largest finite coordinate, so a pole doesn't stitch everything.
+fn largest(coordinates: impl Iterator<Item = f64>) -> f64 {
+    coordinates.filter(|c| c.is_finite()).fold(0.0, |max, c| max.max(c.abs()))
+}
+
+impl Shape {
+    // A polyline through `samples` points of `f` over `range`. A curve that
+    // ends where it starts is closed.
+    pub fn from_parametric_curve(f: impl Fn(f64) -> Point, range: RangeInclusive<f64>, samples: usize) -> Shape {
+        let points: Vec<Point> = steps(&range, samples).map(f).collect();
+        let (edges, faces) = grid(1, points.len());
+        Self::stitched(points, edges, faces, 1)
+    }
+
+    // A surface through `f` sampled in `u_steps` by `v_steps` quads. Seams
+    // where it meets itself, however twisted, are joined, so a sphere, a
+    // torus or a Möbius strip come out in one piece. Faces turn towards
+    // the cross product of the u and v directions.
+    pub fn from_parametric_surface(
+        f: impl Fn(f64, f64) -> Point,
+        u_range: RangeInclusive<f64>,
+        v_range: RangeInclusive<f64>,
+        (u_steps, v_steps): (usize, usize),
+    ) -> Shape {
+        let vs: Vec<f64> = steps(&v_range, v_steps + 1).collect();
+        let points: Vec<Point> = steps(&u_range, u_steps + 1)
+            .flat_map(|u| vs.iter().map(move |v| (u, *v)))
+            .map(|(u, v)| f(u, v))
+            .collect();
+        let rows = points.len() / vs.len();
+        let (edges, faces) = grid(rows, vs.len());
+        Self::stitched(points, edges, faces, rows)
+    }
+
+    fn stitched(points: Vec<Point>, mut edges: Vec<Edge>, mut faces: Vec<Face>, rows: usize) -> Shape {
+        let size = largest(points.iter().flat_map(|p| [p.0, p.1, p.2]));
+        let border = border(rows, points.len() / rows);
+        let same = |a: usize, b: usize| (points[a] - points[b]).magnitude() <= tolerance(size);
+        let kept = stitch(points.len(), &border, same, &mut edges, &mut faces);
+        let mut shape = Shape {
+            vertices: kept.iter().map(|v| points[*v]).collect(),
+            edges,
+            faces,
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
+            center: None,
+        };
+        shape.center = shape.centroid();
+        shape
+    }
+}
+
+impl Shape4 {
+    pub fn from_parametric_curve(f: impl Fn(f64) -> Point4, range: RangeInclusive<f64>, samples: usize) -> Shape4 {
+        let points: Vec<Point4> = steps(&range, samples).map(f).collect();
+        let (edges, faces) = grid(1, points.len());
+        Self::stitched(points, edges, faces, 1)
+    }
+
+    // Like `Shape::from_parametric_surface`, for surfaces such as the
+    // Clifford torus that only close up in 4D.
+    pub fn from_parametric_surface(
+        f: impl Fn(f64, f64) -> Point4,
+        u_range: RangeInclusive<f64>,
+        v_range: RangeInclusive<f64>,
+        (u_steps, v_steps): (usize, usize),
+    ) -> Shape4 {
+        let vs: Vec<f64> = steps(&v_range, v_steps + 1).collect();
+        let points: Vec<Point4> = steps(&u_range, u_steps + 1)
+            .flat_map(|u| vs.iter().map(move |v| (u, *v)))
+            .map(|(u, v)| f(u, v))
+            .collect();
+        let rows = points.len() / vs.len();
+        let (edges, faces) = grid(rows, vs.len());
+        Self::stitched(points, edges, faces, rows)
+    }
+
+    fn stitched(points: Vec<Point4>, mut edges: Vec<Edge>, mut faces: Vec<Face>, rows: usize) -> Shape4 {
+        let size = largest(points.iter().flat_map(|p| [p.0, p.1, p.2, p.3]));
+        let border = border(rows, points.len() / rows);
+        let same = |a: usize, b: usize| (points[a] - points[b]).magnitude() <= tolerance(size);
+        let kept = stitch(points.len(), &border, same, &mut edges, &mut faces);
+        let mut shape = Shape4 {
+            vertices: kept.iter().map(|v| points[*v]).collect(),
+            edges,
+            edge_styles: Vec::new(),
+            faces,
+            facets: Vec::new(),
+            center: None,
+        };
+        shape.center = shape.centroid();
+        shape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, TAU};
+
+    use crate::shapes::{Point, Point4, Shape, Shape4};
+
+    #[test]
+    fn curves() {
+        let helix = Shape::from_parametric_curve(|t| Point(t.cos(), t.sin(), t / 4.0), 0.0..=4.0 * PI, 50);
+        assert_eq!(helix.counts(), (50, 49, 0));
+        // the last sample is the first again
+        let circle = Shape::from_parametric_curve(|t| Point(t.cos(), t.sin(), 0.0), 0.0..=TAU, 50);
+        assert_eq!(circle.counts(), (49, 49, 0));
+        assert_eq!(circle.validate(), Ok(()));
+        assert!(circle.centroid().unwrap().magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn surfaces() {
+        let sphere = |u: f64, v: f64| Point(u.sin() * v.cos(), u.sin() * v.sin(), u.cos());
+        let sphere = Shape::from_parametric_surface(sphere, 0.0..=PI, 0.0..=TAU, (8, 16));
+        // one row of 16 around for each ring but the poles, which are a point
+        assert_eq!(sphere.vertices.len(), 2 + 7 * 16);
+        assert_eq!(sphere.faces.len(), 2 * 16 + 2 * 16 * 6);
+        assert_eq!(sphere.validate(), Ok(()));
+
+        // the seam joins v to -v
+        let mobius = |u: f64, v: f64| {
+            let r = 3.0 + v * (u / 2.0).cos();
+            Point(r * u.cos(), r * u.sin(), v * (u / 2.0).sin())
+        };
+        let strip = Shape::from_parametric_surface(mobius, 0.0..=TAU, -1.0..=1.0, (20, 4));
+        assert_eq!(strip.counts(), (20 * 5, 20 * 5 + 20 * 4, 2 * 20 * 4));
+        assert_eq!(strip.validate(), Ok(()));
+        // a single edge all the way around, twice as long as the strip
+        let border = strip.adjacency().iter().filter(|n| n.len() == 3).count();
+        assert_eq!(border, 2 * 20);
+
+        let clifford = |u: f64, v: f64| Point4(u.cos(), u.sin(), v.cos(), v.sin());
+        let torus = Shape4::from_parametric_surface(clifford, 0.0..=TAU, 0.0..=TAU, (12, 8));
+        assert_eq!((torus.vertices.len(), torus.edges.len(), torus.faces.len()), (96, 192, 192));
+        let knot = Shape4::from_parametric_curve(|t| Point4(t.cos(), t.sin(), 0.0, t), 0.0..=1.0, 10);
+        assert_eq!(knot.edges.len(), 9);
+    }
+
+    #[test]
+    fn non_finite_samples() {
+        // a pole in the middle stitches nothing
+        let pole = Shape::from_parametric_curve(|t| Point(t, 1.0 / t, 0.0), -1.0..=1.0, 3);
+        assert_eq!(pole.counts(), (3, 2, 0));
+        let pole = |u: f64, v: f64| Point(u, v, 1.0 / (u * u + v * v));
+        let surface = Shape::from_parametric_surface(pole, -1.0..=1.0, -1.0..=1.0, (4, 4));
+        assert_eq!(surface.counts(), (25, 40, 32));
+        let knot = Shape4::from_parametric_curve(|t| Point4(t, 0.0, 0.0, 1.0 / t), -1.0..=1.0, 5);
+        assert_eq!(knot.vertices.len(), 5);
+    }
+}
@@ -0,0 +1,356 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    cue::WGradient,
+    scene::Transform,
+    shapes::{Edge, EdgeStyle, Face, Point, Shape},
+    Display, RGB,
+};
+
+// How a plot is laid out. Whatever the function's values, the plot fills a
+// box `size` across and `height` high around the origin, and the labels
+// give the values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotOptions {
+    // quads along x and y
+    pub resolution: (usize, usize),
+    pub size: f64,
+    pub height: f64,
+    // about how many ticks on each axis
+    pub ticks: usize,
+    // the z shown, by default from the lowest value to the highest. Values
+    // outside are left out.
+    pub z_range: Option<RangeInclusive<f64>>,
+    // the surface from low to high
+    pub gradient: WGradient,
+    pub axis_color: RGB,
+    pub label_color: RGB,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            resolution: (40, 40),
+            size: 30.0,
+            height: 15.0,
+            ticks: 5,
+            z_range: None,
+            gradient: WGradient::default(),
+            axis_color: RGB(160, 160, 160),
+            label_color: RGB(220, 220, 220),
+        }
+    }
+}
+
+// Text at a point in space, see `Display::label`.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub at: Point,
+    pub text: String,
+}
+
+// A function sampled into a surface, with axes, ticks and their values.
+pub struct Plot {
+    // the surface, then the axes and ticks
+    pub shape: Shape,
+    pub labels: Vec<Label>,
+    pub label_color: RGB,
+    // the values the box spans
+    pub x_range: RangeInclusive<f64>,
+    pub y_range: RangeInclusive<f64>,
+    pub z_range: RangeInclusive<f64>,
+}
+
+// Round values between the ends of `range` to put ticks at, about `count`
+// of them and 1, 2 or 5 times a power of ten apart, and how many decimals
+// they need.
+fn ticks(range: &RangeInclusive<f64>, count: usize) -> (Vec<f64>, usize) {
+    let (start, end) = (*range.start(), *range.end());
+    let span = end - start;
+    if count == 0 || span <= 0.0 || !span.is_finite() {
+        return (Vec::new(), 0);
+    }
+    let rough = span / count as f64;
+    let power = 10.0_f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].map(|m| m * power).into_iter().find(|s| *s >= rough).unwrap_or(10.0 * power);
+    // a little slack, so the ends count when they are on a step
+    let first = (start / step - 1e-9).ceil() as i64;
+    let last = (end / step + 1e-9).floor() as i64;
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    ((first..=last).map(|k| k as f64 * step).collect(), decimals)
+}
+
+// The range from its lower end to its higher one, widened around a single
+// value so the axis still has a length. `None` when an end isn't finite.
+fn normalized(range: &RangeInclusive<f64>) -> Option<RangeInclusive<f64>> {
+    let (start, end) = (*range.start(), *range.end());
+    if !start.is_finite() || !end.is_finite() {
+        return None;
+    }
+    Some(match (start.min(end), start.max(end)) {
+        (low, high) if low < high => low..=high,
+        (low, high) => low - 1.0..=high + 1.0,
+    })
+}
+
+// Drops the samples `keep` says no to, with the edges and faces around
+// them. The surface has no w, colors or edge styles yet.
+fn keep_samples(shape: &mut Shape, keep: impl Fn(&Point) -> bool) {
+    let mut kept = 0;
+    let index: Vec<Option<usize>> = shape
+        .vertices
+        .iter()
+        .map(|v| {
+            keep(v).then(|| {
+                kept += 1;
+                kept - 1
+            })
+        })
+        .collect();
+    let mut samples = index.iter();
+    shape.vertices.retain(|_| samples.next().is_some_and(Option::is_some));
+    shape.edges.retain_mut(|e| match (index[e.0], index[e.1]) {
+        (Some(a), Some(b)) => {
+            *e = Edge(a, b);
+            true
+        }
+        _ => false,
+    });
+    shape.faces.retain_mut(|f| match (index[f.0], index[f.1], index[f.2]) {
+        (Some(a), Some(b), Some(c)) => {
+            *f = Face(a, b, c);
+            true
+        }
+        _ => false,
+    });
+}
+
+// Where `value` ends up on an axis `size` long centered on 0.
+fn place(value: f64, range: &RangeInclusive<f64>, size: f64) -> f64 {
+    let span = range.end() - range.start();
+    if span > 0.0 { (value - range.start()) / span * size - size / 2.0 } else { 0.0 }
+}
+
+impl Plot {
+    // Samples z = f(x, y) over the ranges. Samples that aren't finite, or are
+    // outside `options.z_range`, are left out with the quads around them, so
+    // poles and gaps show as holes. Reversed ranges are turned around and a
+    // single value is widened by one either way.
+    //
+    // An x or y range with an end that isn't finite can't be sampled, so the
+    // plot only has axes, from -1 to 1 on that side. An infinite end of
+    // `options.z_range` leaves that side open and a NaN one ignores it.
+    pub fn surface(
+        f: impl Fn(f64, f64) -> f64,
+        x_range: RangeInclusive<f64>,
+        y_range: RangeInclusive<f64>,
+        options: &PlotOptions,
+    ) -> Plot {
+        let mut shape = match (normalized(&x_range), normalized(&y_range)) {
+            (Some(x), Some(y)) => Shape::from_parametric_surface(|x, y| Point(x, y, f(x, y)), x, y, options.resolution),
+            _ => Shape::empty(),
+        };
+        let x_range = normalized(&x_range).unwrap_or(-1.0..=1.0);
+        let y_range = normalized(&y_range).unwrap_or(-1.0..=1.0);
+        let z_range = options.z_range.as_ref().and_then(|z| {
+            let (start, end) = (*z.start(), *z.end());
+            match normalized(z) {
+                Some(z) => Some(z),
+                None if start.is_nan() || end.is_nan() => None,
+                None => Some(start.min(end)..=start.max(end)),
+            }
+        });
+        keep_samples(&mut shape, |p| p.2.is_finite() && z_range.as_ref().is_none_or(|z| z.contains(&p.2)));
+        let z_range = z_range.and_then(|z| normalized(&z)).unwrap_or_else(|| {
+            let (low, high) = shape
+                .vertices
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), p| (low.min(p.2), high.max(p.2)));
+            // -1 to 1 when nothing at all is left
+            normalized(&(low..=high)).unwrap_or(-1.0..=1.0)
+        });
+
+        let (size, height) = (options.size, options.height);
+        for v in &mut shape.vertices {
+            *v = Point(place(v.0, &x_range, size), place(v.1, &y_range, size), place(v.2, &z_range, height));
+        }
+        shape.colors = shape.vertices.iter().map(|v| Some(options.gradient.color(v.2, height / 2.0))).collect();
+        shape.center = Some(Point::zero());
+
+        let mut plot = Plot {
+            shape,
+            labels: Vec::new(),
+            label_color: options.label_color,
+            x_range,
+            y_range,
+            z_range,
+        };
+        plot.add_axes(options);
+        plot
+    }
+
+    // Three axes from the lower front left corner of the box, with ticks
+    // pointing away from the surface and their values past the ends.
+    fn add_axes(&mut self, options: &PlotOptions) {
+        let (half, low) = (options.size / 2.0, -options.height / 2.0);
+        let tick = options.size * 0.03;
+        let corner = Point(-half, -half, low);
+        let first = self.shape.vertices.len();
+        self.shape.vertices.push(corner);
+        let axes = [
+            ("x", Point(half, -half, low), self.x_range.clone(), Point(0.0, -tick, 0.0)),
+            ("y", Point(-half, half, low), self.y_range.clone(), Point(-tick, 0.0, 0.0)),
+            ("z", Point(-half, -half, -low), self.z_range.clone(), Point(-tick, 0.0, 0.0)),
+        ];
+        for (name, end, range, out) in axes {
+            let (values, decimals) = ticks(&range, options.ticks);
+            let mut previous = first;
+            for value in values {
+                let at = corner + (end - corner) * ((value - range.start()) / (range.end() - range.start()));
+                previous = self.line(previous, at);
+                self.line(previous, at + out);
+                self.labels.push(Label {
+                    at: at + out * 2.0,
+                    text: format!("{:.*}", decimals, value),
+                });
+            }
+            self.line(previous, end);
+            self.labels.push(Label {
+                at: end + (end - corner).unit() * tick * 2.0,
+                text: name.to_string(),
+            });
+        }
+        self.shape.colors.resize(self.shape.vertices.len(), Some(options.axis_color));
+    }
+
+    // An axis edge from a vertex to a new one, kept on top of the surface.
+    // Returns the vertex it ends at, `from` when it's already there.
+    fn line(&mut self, from: usize, to: Point) -> usize {
+        let shape = &mut self.shape;
+        if (shape.vertices[from] - to).magnitude() <= 1e-9 * to.magnitude().max(1.0) {
+            return from;
+        }
+        shape.vertices.push(to);
+        shape.edges.push(Edge::new(from, shape.vertices.len() - 1));
+        shape.set_edge_style(shape.edges.len() - 1, EdgeStyle::default().with_priority(1));
+        shape.vertices.len() - 1
+    }
+
+    // Moves the plot, labels included.
+    pub fn transform(&mut self, transform: &Transform) {
+        for v in &mut self.shape.vertices {
+            *v = transform.apply(v);
+        }
+        for label in &mut self.labels {
+            label.at = transform.apply(&label.at);
+        }
+        self.shape.center = self.shape.center.map(|c| transform.apply(&c));
+    }
+}
+
+impl Display {
+    pub fn render_plot(&mut self, plot: &Plot) -> &[(f32, u32)] {
+        self.draw_plot(plot);
+        self.fill_frame();
+        self.frame()
+    }
+
+    pub fn draw_plot(&mut self, plot: &Plot) {
+        self.draw(&plot.shape);
+        self.draw_labels(&plot.labels, plot.label_color);
+    }
+
+    // Labels on top of the last frame drawn. Returns how many fit, see
+    // `Display::label`.
+    pub fn draw_labels(&mut self, labels: &[Label], color: RGB) -> usize {
+        labels.iter().filter(|label| self.label(&label.at, &label.text, color)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shapes::Point, Display};
+
+    use super::{ticks, Plot, PlotOptions};
+
+    #[test]
+    fn tick_values() {
+        assert_eq!(ticks(&(-1.0..=1.0), 5), (vec![-1.0, -0.5, 0.0, 0.5, 1.0], 1));
+        assert_eq!(ticks(&(0.0..=100.0), 4), (vec![0.0, 50.0, 100.0], 0));
+        assert_eq!(ticks(&(3.0..=17.0), 3), (vec![5.0, 10.0, 15.0], 0));
+        assert_eq!(ticks(&(1.0..=1.0), 5), (Vec::new(), 0));
+        assert_eq!(ticks(&(0.0..=0.03), 3).1, 2);
+    }
+
+    #[test]
+    fn surface_plot() {
+        let options = PlotOptions {
+            resolution: (10, 10),
+            ticks: 4,
+            ..PlotOptions::default()
+        };
+        let plot = Plot::surface(|x, y| x * y, -2.0..=2.0, 0.0..=4.0, &options);
+        assert_eq!(plot.z_range, -8.0..=8.0);
+        // the surface, the corner, and for each tick a point on the axis and
+        // one at the end of the tick, but x and y start on a tick and end on
+        // one, z goes from -8 to 8 by 5
+        let ticks = 5 + 5 + 3;
+        assert_eq!(plot.shape.vertices.len(), 11 * 11 + 1 + 2 * ticks - 2 + 1);
+        assert_eq!(plot.shape.faces.len(), 2 * 10 * 10);
+        assert_eq!(plot.shape.validate(), Ok(()));
+        assert_eq!(plot.labels.len(), ticks + 3);
+        assert!(plot.labels.iter().any(|l| l.text == "-5"));
+        // the surface spans the box whatever the values
+        let aabb = plot.shape.aabb().unwrap();
+        assert!((aabb.max.2 - 7.5).abs() < 1e-9);
+
+        // a pole leaves a hole
+        let pole = Plot::surface(|x, y| 1.0 / (x * x + y * y), -1.0..=1.0, -1.0..=1.0, &options);
+        assert_eq!(pole.shape.faces.len(), 2 * 10 * 10 - 6);
+        assert!(pole.shape.vertices.iter().all(|v| v.2.is_finite()));
+
+        let mut display = Display::new(100, 50, Point(0.0, -60.0, 20.0), Point(0.0, 3.0, -1.0), 60.0);
+        display.draw_plot(&plot);
+        let text: String = display.cells().iter().map(|c| c.glyph).collect();
+        for label in ["-2", "4", "x", "z"] {
+            assert!(text.contains(label), "{} missing", label);
+        }
+    }
+
+    #[test]
+    fn degenerate_ranges() {
+        let options = PlotOptions {
+            resolution: (4, 4),
+            ..PlotOptions::default()
+        };
+        let reversed = Plot::surface(|x, _| x, 2.0..=-2.0, 1.0..=1.0, &options);
+        assert_eq!((reversed.x_range, reversed.y_range), (-2.0..=2.0, 0.0..=2.0));
+        assert_eq!(reversed.shape.faces.len(), 2 * 4 * 4);
+        assert!(reversed.labels.iter().all(|l| l.at.0.is_finite() && l.at.1.is_finite() && l.at.2.is_finite()));
+
+        // flat, and with nothing left at all
+        let flat = Plot::surface(|_, _| 3.0, 0.0..=1.0, 0.0..=1.0, &options);
+        assert_eq!(flat.z_range, 2.0..=4.0);
+        let empty = Plot::surface(|_, _| f64::NAN, 0.0..=1.0, 0.0..=1.0, &options);
+        assert_eq!((empty.z_range, empty.shape.faces.len()), (-1.0..=1.0, 0));
+        assert_eq!(empty.shape.validate(), Ok(()));
+
+        // ranges that aren't finite don't stop the plot
+        let open = PlotOptions {
+            z_range: Some(1.5..=f64::INFINITY),
+            ..options.clone()
+        };
+        let above = Plot::surface(|x, _| x, 0.0..=2.0, 0.0..=1.0, &open);
+        assert_eq!(above.z_range, 1.5..=2.0);
+        assert!(above.shape.vertices.iter().all(|v| v.0.is_finite()));
+        let ignored = PlotOptions {
+            z_range: Some(f64::NAN..=0.0),
+            ..options.clone()
+        };
+        assert_eq!(Plot::surface(|x, _| x, 0.0..=2.0, 0.0..=1.0, &ignored).z_range, 0.0..=2.0);
+        let unsampled = Plot::surface(|x, _| x, 0.0..=f64::INFINITY, f64::NAN..=1.0, &options);
+        assert_eq!((unsampled.x_range, unsampled.y_range), (-1.0..=1.0, -1.0..=1.0));
+        assert_eq!(unsampled.shape.faces.len(), 0);
+        assert!(unsampled.labels.iter().all(|l| l.at.0.is_finite() && l.at.1.is_finite() && l.at.2.is_finite()));
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::{PI, TAU},
+};
+
+use crate::shapes::{Edge, Face, Point, Shape};
+
+// Golden ratio, the icosahedron and dodecahedron are built on it.
+const PHI: f64 = 1.618_033_988_749_895;
+
+impl Shape {
+    // A sphere of `rings` bands from pole to pole, each split into
+    // `segments` around the z axis.
+    pub fn generate_uv_sphere(center: Point, radius: f64, rings: usize, segments: usize) -> Shape {
+        let (rings, segments) = (rings.max(2), segments.max(3));
+        let mut vertices = vec![center + Point(0.0, 0.0, radius)];
+        for ring in 1..rings {
+            let (sin_t, cos_t) = (PI * ring as f64 / rings as f64).sin_cos();
+            for segment in 0..segments {
+                let (sin_p, cos_p) = (TAU * segment as f64 / segments as f64).sin_cos();
+                vertices.push(center + Point(sin_t * cos_p, sin_t * sin_p, cos_t) * radius);
+            }
+        }
+        vertices.push(center + Point(0.0, 0.0, -radius));
+        let bottom = vertices.len() - 1;
+        // vertex `segment` around of band edge `ring`, 1 being under the top
+        let at = |ring: usize, segment: usize| 1 + (ring - 1) * segments + segment % segments;
+
+        let mut faces = Vec::new();
+        for segment in 0..segments {
+            faces.push(Face(0, at(1, segment), at(1, segment + 1)));
+            for ring in 1..rings - 1 {
+                let quad = [at(ring, segment), at(ring + 1, segment), at(ring + 1, segment + 1), at(ring, segment + 1)];
+                faces.extend(split(quad));
+            }
+            faces.push(Face(bottom, at(rings - 1, segment + 1), at(rings - 1, segment)));
+        }
+        // the bands and the lines of longitude, no diagonals
+        let mut edges = Vec::new();
+        for segment in 0..segments {
+            edges.push(Edge::new(0, at(1, segment)));
+            for ring in 1..rings {
+                edges.push(Edge::new(at(ring, segment), at(ring, segment + 1)));
+                if ring + 1 < rings {
+                    edges.push(Edge::new(at(ring, segment), at(ring + 1, segment)));
+                }
+            }
+            edges.push(Edge::new(at(rings - 1, segment), bottom));
+        }
+        solid(vertices, edges, faces, center)
+    }
+
+    // An icosahedron with every triangle split in four `subdivisions` times,
+    // and the new corners pushed out onto the sphere. Evenly covered, unlike
+    // the uv sphere.
+    pub fn generate_icosphere(center: Point, radius: f64, subdivisions: usize) -> Shape {
+        let base = Self::generate_icosahedron(Point::zero(), 1.0);
+        let mut vertices = base.vertices;
+        let mut faces = base.faces;
+        for _ in 0..subdivisions {
+            let mut middles: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut middle = |a: usize, b: usize, vertices: &mut Vec<Point>| {
+                *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    vertices.push(((vertices[a] + vertices[b]) / 2.0).unit());
+                    vertices.len() - 1
+                })
+            };
+            faces = faces
+                .iter()
+                .flat_map(|f| {
+                    let ab = middle(f.0, f.1, &mut vertices);
+                    let bc = middle(f.1, f.2, &mut vertices);
+                    let ca = middle(f.2, f.0, &mut vertices);
+                    [Face(f.0, ab, ca), Face(f.1, bc, ab), Face(f.2, ca, bc), Face(ab, bc, ca)]
+                })
+                .collect();
+        }
+        let vertices = vertices.into_iter().map(|v| center + v * radius).collect();
+        let mut shape = solid(vertices, Vec::new(), faces, center);
+        shape.edges_from_faces();
+        shape
+    }
+
+    // A donut around the z axis, `segments` around the ring and `sides`
+    // around the tube.
+    pub fn generate_torus(center: Point, major_radius: f64, minor_radius: f64, segments: usize, sides: usize) -> Shape {
+        let (segments, sides) = (segments.max(3), sides.max(3));
+        let mut vertices = Vec::with_capacity(segments * sides);
+        for segment in 0..segments {
+            let (sin_u, cos_u) = (TAU * segment as f64 / segments as f64).sin_cos();
+            for side in 0..sides {
+                let (sin_v, cos_v) = (TAU * side as f64 / sides as f64).sin_cos();
+                let out = major_radius + minor_radius * cos_v;
+                vertices.push(center + Point(out * cos_u, out * sin_u, minor_radius * sin_v));
+            }
+        }
+        let at = |segment: usize, side: usize| (segment % segments) * sides + side % sides;
+        let mut edges = Vec::with_capacity(2 * segments * sides);
+        let mut faces = Vec::with_capacity(2 * segments * sides);
+        for segment in 0..segments {
+            for side in 0..sides {
+                edges.push(Edge::new(at(segment, side), at(segment + 1, side)));
+                edges.push(Edge::new(at(segment, side), at(segment, side + 1)));
+                // counter-clockwise from outside, the tube isn't convex so
+                // `solid` can't orient these
+                let quad = [at(segment, side), at(segment + 1, side), at(segment + 1, side + 1), at(segment, side + 1)];
+                faces.extend(split(quad));
+            }
+        }
+        Shape {
+            vertices,
+            edges,
+            faces,
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
+            center: Some(center),
+        }
+    }
+
+    // Along the z axis with `center` halfway up, closed at both ends.
+    pub fn generate_cylinder(center: Point, radius: f64, height: f64, segments: usize) -> Shape {
+        let segments = segments.max(3);
+        let mut vertices = Vec::with_capacity(2 * segments + 2);
+        for z in [-height / 2.0, height / 2.0] {
+            for segment in 0..segments {
+                let (sin, cos) = (TAU * segment as f64 / segments as f64).sin_cos();
+                vertices.push(center + Point(radius * cos, radius * sin, z));
+            }
+        }
+        // middles of the ends, for their faces only
+        vertices.push(center + Point(0.0, 0.0, -height / 2.0));
+        vertices.push(center + Point(0.0, 0.0, height / 2.0));
+        let (bottom, top) = (2 * segments, 2 * segments + 1);
+        let at = |end: usize, segment: usize| end * segments + segment % segments;
+        let mut edges = Vec::new();
+        let mut faces = Vec::new();
+        for segment in 0..segments {
+            edges.push(Edge::new(at(0, segment), at(0, segment + 1)));
+            edges.push(Edge::new(at(1, segment), at(1, segment + 1)));
+            edges.push(Edge::new(at(0, segment), at(1, segment)));
+            faces.extend(split([at(0, segment), at(0, segment + 1), at(1, segment + 1), at(1, segment)]));
+            faces.push(Face(bottom, at(0, segment), at(0, segment + 1)));
+            faces.push(Face(top, at(1, segment), at(1, segment + 1)));
+        }
+        solid(vertices, edges, faces, center)
+    }
+
+    // Along the z axis, the base `height / 2` below `center` and the tip as
+    // far above it.
+    pub fn generate_cone(center: Point, radius: f64, height: f64, segments: usize) -> Shape {
+        let segments = segments.max(3);
+        let mut vertices: Vec<Point> = (0..segments)
+            .map(|segment| {
+                let (sin, cos) = (TAU * segment as f64 / segments as f64).sin_cos();
+                center + Point(radius * cos, radius * sin, -height / 2.0)
+            })
+            .collect();
+        vertices.push(center + Point(0.0, 0.0, height / 2.0));
+        vertices.push(center + Point(0.0, 0.0, -height / 2.0));
+        let (tip, base) = (segments, segments + 1);
+        let mut edges = Vec::new();
+        let mut faces = Vec::new();
+        for segment in 0..segments {
+            let next = (segment + 1) % segments;
+            edges.push(Edge::new(segment, next));
+            edges.push(Edge::new(segment, tip));
+            faces.push(Face(segment, next, tip));
+            faces.push(Face(base, segment, next));
+        }
+        solid(vertices, edges, faces, center)
+    }
+
+    // A square base of side `base`, `height / 2` below `center`, and the
+    // tip as far above it.
+    pub fn generate_pyramid(center: Point, base: f64, height: f64) -> Shape {
+        let (half, z) = (base / 2.0, height / 2.0);
+        let vertices = vec![
+            center + Point(-half, -half, -z),
+            center + Point(half, -half, -z),
+            center + Point(half, half, -z),
+            center + Point(-half, half, -z),
+            center + Point(0.0, 0.0, z),
+        ];
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 4), (1, 4), (2, 4), (3, 4)]
+            .map(|(a, b)| Edge::new(a, b))
+            .to_vec();
+        let mut faces = split([0, 1, 2, 3]).to_vec();
+        faces.extend((0..4).map(|k| Face(k, (k + 1) % 4, 4)));
+        solid(vertices, edges, faces, center)
+    }
+
+    // The Platonic solids, with all vertices `radius` from `center`.
+    // `generate_cube` is the fifth, sized by its side instead.
+
+    pub fn generate_tetrahedron(center: Point, radius: f64) -> Shape {
+        let corners = [(1.0, 1.0, 1.0), (1.0, -1.0, -1.0), (-1.0, 1.0, -1.0), (-1.0, -1.0, 1.0)];
+        platonic(center, radius, &corners, &[&[0, 1, 2], &[0, 1, 3], &[0, 2, 3], &[1, 2, 3]])
+    }
+
+    pub fn generate_octahedron(center: Point, radius: f64) -> Shape {
+        let corners = [
+            (1.0, 0.0, 0.0),
+            (-1.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, -1.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (0.0, 0.0, -1.0),
+        ];
+        let mut faces: Vec<[usize; 3]> = Vec::new();
+        for x in [0, 1] {
+            for y in [2, 3] {
+                for z in [4, 5] {
+                    faces.push([x, y, z]);
+                }
+            }
+        }
+        let faces: Vec<&[usize]> = faces.iter().map(|f| &f[..]).collect();
+        platonic(center, radius, &corners, &faces)
+    }
+
+    pub fn generate_icosahedron(center: Point, radius: f64) -> Shape {
+        // three golden rectangles at right angles to each other
+        let mut corners = Vec::with_capacity(12);
+        for a in [-1.0, 1.0] {
+            for b in [-PHI, PHI] {
+                corners.push((0.0, a, b));
+                corners.push((a, b, 0.0));
+                corners.push((b, 0.0, a));
+            }
+        }
+        // every three corners the length of an edge apart
+        let faces = by_distance(&corners, 3, 2.0);
+        let faces: Vec<&[usize]> = faces.iter().map(|f| &f[..]).collect();
+        platonic(center, radius, &corners, &faces)
+    }
+
+    pub fn generate_dodecahedron(center: Point, radius: f64) -> Shape {
+        // the dual of the icosahedron, a corner in the middle of each of its
+        // triangles and a pentagon around each of its vertices
+        let dual = Self::generate_icosahedron(Point::zero(), 1.0);
+        let points: Vec<Point> = dual
+            .faces
+            .iter()
+            .map(|f| dual.vertices[f.0] + dual.vertices[f.1] + dual.vertices[f.2])
+            .collect();
+        let faces: Vec<Vec<usize>> = dual
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(v, vertex)| {
+                let touching: Vec<usize> = (0..dual.faces.len())
+                    .filter(|f| [dual.faces[*f].0, dual.faces[*f].1, dual.faces[*f].2].contains(&v))
+                    .collect();
+                around(&points, &touching, *vertex)
+            })
+            .collect();
+        let corners: Vec<(f64, f64, f64)> = points.iter().map(|p| (p.0, p.1, p.2)).collect();
+        let faces: Vec<&[usize]> = faces.iter().map(|f| &f[..]).collect();
+        platonic(center, radius, &corners, &faces)
+    }
+}
+
+// Two triangles of a quad given counter-clockwise.
+fn split([a, b, c, d]: [usize; 4]) -> [Face; 2] {
+    [Face(a, b, c), Face(a, c, d)]
+}
+
+// A convex shape around `center`, with its faces turned to face outwards.
+fn solid(vertices: Vec<Point>, edges: Vec<Edge>, faces: Vec<Face>, center: Point) -> Shape {
+    let faces = faces
+        .into_iter()
+        .map(|f| {
+            let [a, b, c] = [f.0, f.1, f.2].map(|v| vertices[v]);
+            let outwards = (b - a).cross(&(c - a)).dot(&((a + b + c) / 3.0 - center)) >= 0.0;
+            if outwards { f } else { Face(f.0, f.2, f.1) }
+        })
+        .collect();
+    Shape {
+        vertices,
+        edges,
+        faces,
+        w: Vec::new(),
+        colors: Vec::new(),
+        edge_styles: Vec::new(),
+        center: Some(center),
+    }
+}
+
+// Scales corners out to `radius`, with the polygons as fans of triangles and
+// only their sides as edges.
+fn platonic(center: Point, radius: f64, corners: &[(f64, f64, f64)], polygons: &[&[usize]]) -> Shape {
+    let vertices: Vec<Point> = corners.iter().map(|c| center + Point(c.0, c.1, c.2).unit() * radius).collect();
+    let mut edges = Vec::new();
+    let mut faces = Vec::new();
+    // each side is shared by two polygons
+    let mut sides = HashSet::new();
+    for polygon in polygons {
+        for (k, v) in polygon.iter().enumerate() {
+            let next = polygon[(k + 1) % polygon.len()];
+            if sides.insert((next.min(*v), next.max(*v))) {
+                edges.push(Edge::new(*v, next));
+            }
+        }
+        faces.extend((1..polygon.len() - 1).map(|k| Face(polygon[0], polygon[k], polygon[k + 1])));
+    }
+    solid(vertices, edges, faces, center)
+}
+
+// Every set of `size` corners that are all `distance` from each other.
+fn by_distance(corners: &[(f64, f64, f64)], size: usize, distance: f64) -> Vec<Vec<usize>> {
+    let point = |i: usize| Point(corners[i].0, corners[i].1, corners[i].2);
+    let near = |a: usize, b: usize| ((point(a) - point(b)).magnitude() - distance).abs() < 1e-9;
+    let mut sets: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in 0..size {
+        sets = sets
+            .into_iter()
+            .flat_map(|set| {
+                let start = set.last().map_or(0, |l| l + 1);
+                (start..corners.len())
+                    .filter(|c| set.iter().all(|s| near(*s, *c)))
+                    .map(|c| {
+                        let mut next = set.clone();
+                        next.push(c);
+                        next
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    sets
+}
+
+// The corners in order around `axis`.
+fn around(points: &[Point], corners: &[usize], axis: Point) -> Vec<usize> {
+    let middle = corners.iter().fold(Point::zero(), |sum, c| sum + points[*c]) / corners.len() as f64;
+    // any direction at right angles to the axis to measure angles from
+    let first = points[corners[0]] - middle;
+    let side = axis.cross(&first);
+    let angle = |c: &usize| {
+        let d = points[*c] - middle;
+        d.dot(&side).atan2(d.dot(&first))
+    };
+    let mut sorted = corners.to_vec();
+    sorted.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::{Point, Shape};
+
+    // Every face turns away from `center`.
+    fn outwards(shape: &Shape, center: Point) -> bool {
+        shape.faces.iter().all(|f| {
+            let [a, b, c] = [f.0, f.1, f.2].map(|v| shape.vertices[v]);
+            (b - a).cross(&(c - a)).dot(&((a + b + c) / 3.0 - center)) > 0.0
+        })
+    }
+
+    #[test]
+    fn platonic_solids() {
+        let center = Point(1.0, -2.0, 3.0);
+        for (shape, expected) in [
+            (Shape::generate_tetrahedron(center, 2.0), (4, 6, 4)),
+            (Shape::generate_octahedron(center, 2.0), (6, 12, 8)),
+            (Shape::generate_icosahedron(center, 2.0), (12, 30, 20)),
+            // pentagons are three triangles each
+            (Shape::generate_dodecahedron(center, 2.0), (20, 30, 36)),
+        ] {
+            assert_eq!(shape.counts(), expected);
+            assert_eq!(shape.validate(), Ok(()));
+            assert!(outwards(&shape, center));
+            assert!(shape.vertices.iter().all(|v| ((*v - center).magnitude() - 2.0).abs() < 1e-9));
+            // all edges the same length
+            let length = |e: usize| (shape.vertices[shape.edges[e].0] - shape.vertices[shape.edges[e].1]).magnitude();
+            assert!((0..shape.edges.len()).all(|e| (length(e) - length(0)).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn round_shapes() {
+        let center = Point(0.0, 5.0, 0.0);
+        let sphere = Shape::generate_uv_sphere(center, 3.0, 6, 8);
+        assert_eq!(sphere.counts(), (2 + 5 * 8, 8 * (2 + 5 + 4), 2 * 8 + 2 * 8 * 4));
+        let icosphere = Shape::generate_icosphere(center, 3.0, 2);
+        assert_eq!(icosphere.counts(), (162, 480, 320));
+        let cylinder = Shape::generate_cylinder(center, 2.0, 4.0, 12);
+        assert_eq!(cylinder.counts(), (26, 36, 48));
+        let cone = Shape::generate_cone(center, 2.0, 4.0, 12);
+        assert_eq!(cone.counts(), (14, 24, 24));
+        let pyramid = Shape::generate_pyramid(center, 2.0, 4.0);
+        assert_eq!(pyramid.counts(), (5, 8, 6));
+        for shape in [&sphere, &icosphere, &cylinder, &cone, &pyramid] {
+            assert_eq!(shape.validate(), Ok(()));
+            assert!(outwards(shape, center));
+        }
+        assert!(icosphere.vertices.iter().all(|v| ((*v - center).magnitude() - 3.0).abs() < 1e-9));
+
+        let torus = Shape::generate_torus(center, 5.0, 1.0, 24, 12);
+        assert_eq!(torus.counts(), (288, 576, 576));
+        assert_eq!(torus.validate(), Ok(()));
+        // away from the middle of the tube rather than the torus
+        assert!(torus.faces.iter().all(|f| {
+            let [a, b, c] = [f.0, f.1, f.2].map(|v| torus.vertices[v]);
+            let middle = (a + b + c) / 3.0 - center;
+            let tube = Point(middle.0, middle.1, 0.0).unit() * 5.0;
+            (b - a).cross(&(c - a)).dot(&(middle - tube)) > 0.0
+        }));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{font, Cell, RGB};
+
+// An image made of real pixels, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<RGB>,
+}
+
+impl Raster {
+    pub fn new(width: usize, height: usize, fill: RGB) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    // Reallocates only when the size changes.
+    pub fn reset(&mut self, width: usize, height: usize, fill: RGB) {
+        self.width = width;
+        self.height = height;
+        self.pixels.clear();
+        self.pixels.resize(width * height, fill);
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<RGB> {
+        if x >= self.width {
+            return None;
+        }
+        self.pixels.get(y * self.width + x).copied()
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: RGB) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: RGB) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.pixels[py * self.width + px] = color;
+            }
+        }
+    }
+
+    // Every cell becomes a `scale.0` x `scale.1` block of pixels in its
+    // foreground color, or the background when nothing is drawn there.
+    pub fn draw_cells(&mut self, cells: &[Cell], width: usize, scale: (usize, usize), background: RGB) {
+        let width = width.max(1);
+        let height = cells.len() / width;
+        self.reset(width * scale.0, height * scale.1, background);
+        for (i, cell) in cells.iter().enumerate() {
+            let color = if cell.glyph == ' ' {
+                cell.bg.unwrap_or(background)
+            } else {
+                cell.fg.unwrap_or(RGB(255, 255, 255))
+            };
+            self.fill_rect((i % width) * scale.0, (i / width) * scale.1, scale.0, scale.1, color);
+        }
+    }
+
+    // A "screenshot" of the cells, each drawn with the built-in bitmap font in
+    // an 8x16 pixel box like a terminal would.
+    pub fn draw_text(&mut self, cells: &[Cell], width: usize, background: RGB) {
+        const CELL: (usize, usize) = (font::WIDTH, font::HEIGHT * 2);
+        let width = width.max(1);
+        let height = cells.len() / width;
+        self.reset(width * CELL.0, height * CELL.1, background);
+        for (i, cell) in cells.iter().enumerate() {
+            let (x0, y0) = ((i % width) * CELL.0, (i / width) * CELL.1);
+            if let Some(bg) = cell.bg {
+                self.fill_rect(x0, y0, CELL.0, CELL.1, bg);
+            }
+            let fg = cell.fg.unwrap_or(RGB(255, 255, 255));
+            for (row, bits) in font::glyph(cell.glyph).iter().enumerate() {
+                // bold smears every row one pixel to the right
+                let bits = if cell.bold { bits | bits << 1 } else { *bits };
+                for col in 0..CELL.0 {
+                    if bits >> col & 1 == 1 {
+                        self.fill_rect(x0 + col, y0 + row * 2, 1, 2, fg);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn from_cells(cells: &[Cell], width: usize, scale: (usize, usize), background: RGB) -> Self {
+        let mut raster = Self::new(0, 0, background);
+        raster.draw_cells(cells, width, scale, background);
+        raster
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    ansi::AnsiEncoder,
+    color::{self, ColorDepth},
+    html::HtmlRecorder,
+    raster::Raster,
+    Cell, RGB,
+};
+
+// Records frames as an asciicast v2 file that asciinema can play back. Frames
+// are stored as the incremental ANSI output, with their timing.
+pub struct AsciicastRecorder<W: Write> {
+    out: W,
+    encoder: AnsiEncoder,
+}
+
+impl<W: Write> AsciicastRecorder<W> {
+    pub fn new(mut out: W, columns: usize, rows: usize) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"env\": {{\"TERM\": \"xterm-256color\"}}}}",
+            columns, rows, timestamp
+        )?;
+        Ok(Self {
+            out,
+            encoder: AnsiEncoder::incremental(),
+        })
+    }
+
+    // `at` is the time since the start of the recording.
+    pub fn frame(&mut self, cells: &[Cell], columns: usize, at: Duration) -> io::Result<()> {
+        let data = self.encoder.encode_to_string(cells, columns);
+        if data.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "[{:.6}, \"o\", \"{}\"]", at.as_secs_f64(), json_escape(&data))
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+pub(crate) fn json_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Records frames as an endlessly looping animated GIF, each cell drawn with the
+// built-in bitmap font. Colors use the 256 color xterm palette.
+//
+// A frame's delay is only known once the next one arrives, so frames are
+// written one behind. Frames closer than `MIN_DELAY` to the previous one are
+// skipped, GIF viewers don't show them anyway.
+pub struct GifRecorder<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    raster: Raster,
+    indices: HashMap<RGB, u8>,
+    // image data and time of the frame not written yet
+    pending: Option<(Vec<u8>, Duration)>,
+    last_delay: Duration,
+}
+
+impl<W: Write> GifRecorder<W> {
+    const MIN_DELAY: Duration = Duration::from_millis(40);
+    const BACKGROUND: RGB = RGB(0, 0, 0);
+
+    pub fn new(mut out: W, columns: usize, rows: usize) -> io::Result<Self> {
+        let mut probe = Raster::new(0, 0, Self::BACKGROUND);
+        probe.draw_text(&vec![Cell::EMPTY; columns * rows], columns, Self::BACKGROUND);
+        let (width, height) = (probe.width, probe.height);
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        // global color table of 256 colors, background color 0
+        out.write_all(&[0xF7, 0, 0])?;
+        for i in 0..=255 {
+            let c = color::ansi_256(i);
+            out.write_all(&[c.0, c.1, c.2])?;
+        }
+        // loop forever
+        out.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self {
+            out,
+            width,
+            height,
+            raster: probe,
+            indices: HashMap::new(),
+            pending: None,
+            last_delay: Self::MIN_DELAY,
+        })
+    }
+
+    // `at` is the time since the start of the recording.
+    pub fn frame(&mut self, cells: &[Cell], columns: usize, at: Duration) -> io::Result<()> {
+        if let Some((_, previous)) = &self.pending
+            && at.saturating_sub(*previous) < Self::MIN_DELAY
+        {
+            return Ok(());
+        }
+        self.raster.draw_text(cells, columns, Self::BACKGROUND);
+        // frames of a different size are cropped or padded
+        let mut image = vec![0u8; self.width * self.height];
+        // runs of one color are common, so those skip the lookup
+        let mut last: Option<(RGB, u8)> = None;
+        for y in 0..self.height.min(self.raster.height) {
+            for x in 0..self.width.min(self.raster.width) {
+                let c = self.raster.pixels[y * self.raster.width + x];
+                let index = match last {
+                    Some((color, index)) if color == c => index,
+                    _ => *self.indices.entry(c).or_insert_with(|| ColorDepth::Ansi256.index(c).unwrap_or(0)),
+                };
+                last = Some((c, index));
+                image[y * self.width + x] = index;
+            }
+        }
+        if let Some((previous, time)) = self.pending.take() {
+            self.last_delay = at.saturating_sub(time);
+            self.write_image(&previous, self.last_delay)?;
+        }
+        self.pending = Some((image, at));
+        Ok(())
+    }
+
+    fn write_image(&mut self, image: &[u8], delay: Duration) -> io::Result<()> {
+        let centiseconds = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        // graphic control extension with the delay
+        self.out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.out.write_all(&centiseconds.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+        // image descriptor covering the whole screen
+        self.out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.out.write_all(&(self.width as u16).to_le_bytes())?;
+        self.out.write_all(&(self.height as u16).to_le_bytes())?;
+        self.out.write_all(&[0x00, 8])?;
+        for block in lzw(image, 8).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some((image, _)) = self.pending.take() {
+            self.write_image(&image, self.last_delay)?;
+        }
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// Variable length LZW as used by GIF, codes packed least significant bit first.
+fn lzw(data: &[u8], min_code_size: u32) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u32, 0u32);
+    let mut push = |code: u16, size: u32| {
+        bits |= (code as u32) << count;
+        count += size;
+        while count >= 8 {
+            bytes.push(bits as u8);
+            bits >>= 8;
+            count -= 8;
+        }
+    };
+
+    // the code for a prefix code followed by a byte at prefix * 256 + byte,
+    // 0 when there is none yet. Only the entries in use are cleared again.
+    let mut table = vec![0u16; MAX_CODE as usize * 256];
+    let mut used: Vec<usize> = Vec::with_capacity(MAX_CODE as usize);
+    let mut next = end + 1;
+    let mut size = min_code_size + 1;
+    push(clear, size);
+
+    let mut prefix: Option<u16> = None;
+    for k in data {
+        let Some(p) = prefix else {
+            prefix = Some(*k as u16);
+            continue;
+        };
+        let entry = p as usize * 256 + *k as usize;
+        if table[entry] != 0 {
+            prefix = Some(table[entry]);
+            continue;
+        }
+        push(p, size);
+        if next < MAX_CODE {
+            table[entry] = next;
+            used.push(entry);
+            next += 1;
+            if next > (1 << size) && size < 12 {
+                size += 1;
+            }
+        } else {
+            push(clear, size);
+            for entry in used.drain(..) {
+                table[entry] = 0;
+            }
+            next = end + 1;
+            size = min_code_size + 1;
+        }
+        prefix = Some(*k as u16);
+    }
+    if let Some(p) = prefix {
+        push(p, size);
+    }
+    push(end, size);
+    if count > 0 {
+        bytes.push(bits as u8);
+    }
+    bytes
+}
+
+// Any kind of recording, picked from the file extension: .cast, .gif or .html.
+pub enum Recorder {
+    Asciicast(AsciicastRecorder<BufWriter<File>>),
+    Gif(GifRecorder<BufWriter<File>>),
+    Html(HtmlRecorder<BufWriter<File>>),
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, columns: usize, rows: usize) -> io::Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("cast") => {
+                let out = BufWriter::new(File::create(path)?);
+                Ok(Self::Asciicast(AsciicastRecorder::new(out, columns, rows)?))
+            }
+            Some("gif") => {
+                let out = BufWriter::new(File::create(path)?);
+                Ok(Self::Gif(GifRecorder::new(out, columns, rows)?))
+            }
+            Some("html") => Ok(Self::Html(HtmlRecorder::new(BufWriter::new(File::create(path)?)))),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unsupported recording format, use .cast, .gif or .html",
+            )),
+        }
+    }
+
+    pub fn frame(&mut self, cells: &[Cell], columns: usize, at: Duration) -> io::Result<()> {
+        match self {
+            Self::Asciicast(r) => r.frame(cells, columns, at),
+            Self::Gif(r) => r.frame(cells, columns, at),
+            Self::Html(r) => {
+                r.frame(cells, columns, at);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Asciicast(r) => r.finish().map(|_| ()),
+            Self::Gif(r) => r.finish().map(|_| ()),
+            Self::Html(r) => r.finish().map(|_| ()),
+        }
+    }
+}
+
+// Records the first frames of a program run with `--record <file>`, where the
+// file is a .cast, .gif or .html. Frames are timed from when it's created.
+pub struct ArgsRecorder {
+    recorder: Option<Recorder>,
+    columns: usize,
+    start: Instant,
+    frames: usize,
+}
+
+impl ArgsRecorder {
+    // `None` when there is no --record argument.
+    pub fn from_args(columns: usize, rows: usize, frames: usize) -> io::Result<Option<Self>> {
+        let Some(path) = std::env::args().skip_while(|arg| arg != "--record").nth(1) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            recorder: Some(Recorder::create(path, columns, rows)?),
+            columns,
+            start: Instant::now(),
+            frames,
+        }))
+    }
+
+    // Records a frame, and once `frames` of them are in finishes the file
+    // and returns true. Frames after that are ignored.
+    pub fn frame_or_done(&mut self, cells: &[Cell]) -> io::Result<bool> {
+        let Some(recorder) = &mut self.recorder else {
+            return Ok(true);
+        };
+        recorder.frame(cells, self.columns, self.start.elapsed())?;
+        self.frames = self.frames.saturating_sub(1);
+        if self.frames > 0 {
+            return Ok(false);
+        }
+        self.recorder.take().map_or(Ok(()), Recorder::finish)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{Cell, RGB};
+
+    use super::{lzw, ArgsRecorder, AsciicastRecorder, GifRecorder, Recorder};
+
+    // What a GIF viewer does with the output of `lzw`.
+    fn unlzw(bytes: &[u8], min_code_size: u32) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let roots = || (0..end + 1).map(|i| vec![i as u8]).collect::<Vec<_>>();
+        let (mut table, mut size) = (roots(), min_code_size + 1);
+        let (mut bits, mut count, mut bytes) = (0u32, 0u32, bytes.iter());
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            while count < size {
+                bits |= (*bytes.next().expect("no end code") as u32) << count;
+                count += 8;
+            }
+            let code = (bits & ((1 << size) - 1)) as usize;
+            bits >>= size;
+            count -= size;
+            if code == clear {
+                (table, size, previous) = (roots(), min_code_size + 1, None);
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => [p.as_slice(), &p[..1]].concat(),
+                (None, None) => panic!("unknown code {}", code),
+            };
+            if let Some(p) = previous
+                && table.len() < 4096
+            {
+                table.push([p.as_slice(), &entry[..1]].concat());
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        // long runs, and noise that fills the code table a few times over
+        let runs: Vec<u8> = (0..20_000).map(|i| (i / 700 % 3) as u8).collect();
+        let mut state = 1u32;
+        let noise: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        for data in [runs, noise, Vec::new()] {
+            assert_eq!(unlzw(&lzw(&data, 8), 8), data);
+        }
+    }
+
+    #[test]
+    fn asciicast() {
+        let mut recorder = AsciicastRecorder::new(Vec::new(), 2, 1).unwrap();
+        let mut cells = [Cell::EMPTY; 2];
+        recorder.frame(&cells, 2, Duration::ZERO).unwrap();
+        cells[1] = Cell {
+            glyph: '"',
+            fg: Some(RGB(1, 2, 3)),
+            ..Cell::EMPTY
+        };
+        recorder.frame(&cells, 2, Duration::from_millis(1500)).unwrap();
+        let out = String::from_utf8(recorder.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 2, \"height\": 1,"));
+        assert_eq!(
+            lines[2],
+            "[1.500000, \"o\", \"\\u001b[1;2H\\u001b[0;38;2;1;2;3m\\\"\\u001b[0m\"]"
+        );
+    }
+
+    #[test]
+    fn stops_after_frames() {
+        let path = std::env::temp_dir().join(format!("asciigraphix-{}.cast", std::process::id()));
+        let mut recording = ArgsRecorder {
+            recorder: Some(Recorder::create(&path, 2, 1).unwrap()),
+            columns: 2,
+            start: Instant::now(),
+            frames: 2,
+        };
+        let mut cells = [Cell::EMPTY; 2];
+        assert!(!recording.frame_or_done(&cells).unwrap());
+        cells[0].glyph = '#';
+        assert!(recording.frame_or_done(&cells).unwrap());
+        assert!(recording.frame_or_done(&cells).unwrap());
+        // the header and the two frames, written out when done
+        let out = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(out.lines().count(), 3);
+    }
+
+    #[test]
+    fn gif_structure() {
+        let mut recorder = GifRecorder::new(Vec::new(), 3, 2).unwrap();
+        let cells = [Cell {
+            glyph: 'x',
+            fg: Some(RGB(255, 0, 0)),
+            ..Cell::EMPTY
+        }; 6];
+        recorder.frame(&cells, 3, Duration::ZERO).unwrap();
+        // too close to the previous frame
+        recorder.frame(&cells, 3, Duration::from_millis(10)).unwrap();
+        recorder.frame(&cells, 3, Duration::from_millis(100)).unwrap();
+        let out = recorder.finish().unwrap();
+        assert!(out.starts_with(b"GIF89a\x18\x00\x20\x00"));
+        assert_eq!(out.last(), Some(&0x3B));
+        // two frames, the first one shown for 100ms
+        let frames: Vec<_> = out.windows(4).filter(|w| w == b"\x21\xF9\x04\x00").collect();
+        assert_eq!(frames.len(), 2);
+        assert!(out.windows(6).any(|w| w == b"\x21\xF9\x04\x00\x0A\x00"));
+    }
+}
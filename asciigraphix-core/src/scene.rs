@@ -0,0 +1,538 @@
+use std::cell::{Cell, OnceCell};
+
+use crate::{
+    bounds::BoundingSphere,
+    shapes::{EdgeStyle, Point, Shape},
+    Display, RGB,
+};
+
+// An affine transform, a 3x3 matrix and a translation in the last column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub matrix: [[f64; 4]; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        matrix: [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]],
+    };
+
+    pub fn translation(by: Point) -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 0.0, by.0], [0.0, 1.0, 0.0, by.1], [0.0, 0.0, 1.0, by.2]],
+        }
+    }
+
+    pub fn scale(factor: f64) -> Self {
+        Self {
+            matrix: [
+                [factor, 0.0, 0.0, 0.0],
+                [0.0, factor, 0.0, 0.0],
+                [0.0, 0.0, factor, 0.0],
+            ],
+        }
+    }
+
+    // Same angles and order as `Shape::rotate`: about y, then x, then z.
+    pub fn rotation((xrot, yrot, zrot): (f64, f64, f64)) -> Self {
+        let (sin_x, cos_x) = xrot.sin_cos();
+        let (sin_y, cos_y) = yrot.sin_cos();
+        let (sin_z, cos_z) = zrot.sin_cos();
+        let y = Self {
+            matrix: [[cos_y, 0.0, sin_y, 0.0], [0.0, 1.0, 0.0, 0.0], [-sin_y, 0.0, cos_y, 0.0]],
+        };
+        let x = Self {
+            matrix: [[1.0, 0.0, 0.0, 0.0], [0.0, cos_x, -sin_x, 0.0], [0.0, sin_x, cos_x, 0.0]],
+        };
+        let z = Self {
+            matrix: [[cos_z, sin_z, 0.0, 0.0], [-sin_z, cos_z, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]],
+        };
+        y.then(&x).then(&z)
+    }
+
+    pub fn rotation_about(pivot: Point, angles: (f64, f64, f64)) -> Self {
+        Self::translation(Point::zero() - pivot)
+            .then(&Self::rotation(angles))
+            .then(&Self::translation(pivot))
+    }
+
+    // This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        let (a, b) = (&next.matrix, &self.matrix);
+        let mut matrix = [[0.0; 4]; 3];
+        for (r, row) in matrix.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| a[r][k] * b[k][c]).sum::<f64>();
+            }
+            row[3] += a[r][3];
+        }
+        Transform { matrix }
+    }
+
+    // How much longer the transform makes things at most. Exact for the
+    // rotations, translations and scales here, skews can stretch more.
+    pub fn max_scale(&self) -> f64 {
+        let m = &self.matrix;
+        (0..3)
+            .map(|c| (m[0][c].powi(2) + m[1][c].powi(2) + m[2][c].powi(2)).sqrt())
+            .fold(0.0, f64::max)
+    }
+
+    pub fn apply(&self, p: &Point) -> Point {
+        let m = &self.matrix;
+        let row = |r: usize| m[r][0] * p.0 + m[r][1] * p.1 + m[r][2] * p.2 + m[r][3];
+        Point(row(0), row(1), row(2))
+    }
+}
+
+// How a node's shape is drawn, `None` keeps what the shape or display has.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Material {
+    pub color: Option<RGB>,
+    pub edges: Option<EdgeStyle>,
+}
+
+// A shape that keeps its bounding sphere, so culling it every frame doesn't
+// go over all its vertices. The sphere is found again after `shape_mut`.
+#[derive(Debug)]
+pub struct Model {
+    shape: Shape,
+    sphere: OnceCell<Option<BoundingSphere>>,
+}
+
+impl Model {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            sphere: OnceCell::new(),
+        }
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn shape_mut(&mut self) -> &mut Shape {
+        self.sphere.take();
+        &mut self.shape
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        *self.sphere.get_or_init(|| self.shape.bounding_sphere())
+    }
+}
+
+impl From<Shape> for Model {
+    fn from(shape: Shape) -> Self {
+        Self::new(shape)
+    }
+}
+
+// One copy of a shape for `Display::render_instances`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Instance {
+    pub transform: Transform,
+    // overrides the shape's colors
+    pub color: Option<RGB>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+// A simpler version of a node's shape, drawn instead when the shape's radius
+// on screen is less than `below` cells.
+#[derive(Debug)]
+pub struct Lod {
+    pub below: f64,
+    pub shape: Shape,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    shape: Model,
+    // relative to the parent
+    pub transform: Transform,
+    // hidden nodes hide their children too
+    pub visible: bool,
+    pub material: Material,
+    // less detailed shapes for when it's small on screen, in any order
+    pub lods: Vec<Lod>,
+    parent: Option<NodeId>,
+}
+
+impl Node {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn shape(&self) -> &Shape {
+        self.shape.shape()
+    }
+
+    pub fn shape_mut(&mut self) -> &mut Shape {
+        self.shape.shape_mut()
+    }
+
+    // Of the shape in the node's own space, kept until the shape changes.
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        self.shape.bounding_sphere()
+    }
+
+    // The least detailed shape good enough for `size` cells of radius.
+    pub fn shape_for(&self, size: f64) -> &Shape {
+        self.lods
+            .iter()
+            .filter(|lod| size < lod.below)
+            .min_by(|a, b| a.below.total_cmp(&b.below))
+            .map_or(self.shape(), |lod| &lod.shape)
+    }
+}
+
+// Shapes placed in a hierarchy of transforms. Parents always come before
+// their children, so there are no cycles.
+#[derive(Debug, Default)]
+pub struct Scene {
+    nodes: Vec<Node>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, shape: Shape) -> NodeId {
+        self.push(name, shape, None)
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, name: &str, shape: Shape) -> NodeId {
+        self.push(name, shape, Some(parent))
+    }
+
+    // A node without a shape of its own, to move its children together.
+    pub fn add_group(&mut self, name: &str, parent: Option<NodeId>) -> NodeId {
+        self.push(name, Shape::empty(), parent)
+    }
+
+    fn push(&mut self, name: &str, shape: Shape, parent: Option<NodeId>) -> NodeId {
+        self.nodes.push(Node {
+            name: name.to_string(),
+            shape: Model::new(shape),
+            transform: Transform::IDENTITY,
+            visible: true,
+            material: Material::default(),
+            lods: Vec::new(),
+            parent,
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+
+    // The first node with that name.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|n| n.name == name).map(NodeId)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes.iter().enumerate().map(|(i, n)| (NodeId(i), n))
+    }
+
+    // From the node's space to the scene's.
+    pub fn world_transform(&self, id: NodeId) -> Transform {
+        let node = self.node(id);
+        match node.parent {
+            Some(parent) => node.transform.then(&self.world_transform(parent)),
+            None => node.transform,
+        }
+    }
+
+    // Whether the node and all its parents are visible.
+    pub fn is_visible(&self, id: NodeId) -> bool {
+        let node = self.node(id);
+        node.visible && node.parent.is_none_or(|p| self.is_visible(p))
+    }
+
+    // All visible shapes in scene space as one, with the materials applied.
+    pub fn flatten(&self) -> Shape {
+        let mut shape = Shape::empty();
+        self.flatten_into(&mut shape);
+        shape
+    }
+
+    // Same as flatten but reuses the buffers of an existing shape.
+    pub fn flatten_into(&self, shape: &mut Shape) {
+        self.flatten_with(shape, |node, _| Some(node.shape()));
+    }
+
+    // Flattens the shapes `pick` gives for each visible node and its world
+    // transform, leaving out the nodes it gives none for.
+    fn flatten_with<'a>(&'a self, shape: &mut Shape, pick: impl Fn(&'a Node, &Transform) -> Option<&'a Shape>) {
+        shape.clear();
+
+        // parents come first, so theirs are always done
+        let mut world: Vec<(Transform, bool)> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            world.push(match node.parent {
+                Some(p) => (node.transform.then(&world[p.0].0), node.visible && world[p.0].1),
+                None => (node.transform, node.visible),
+            });
+        }
+        for (node, (transform, visible)) in self.nodes.iter().zip(world) {
+            if !visible {
+                continue;
+            }
+            let Some(picked) = pick(node, &transform) else {
+                continue;
+            };
+            let first = shape.vertices.len();
+            let first_edge = shape.edges.len();
+            shape.append(picked);
+            for v in &mut shape.vertices[first..] {
+                *v = transform.apply(v);
+            }
+            if let Some(style) = node.material.edges {
+                for edge in first_edge..shape.edges.len() {
+                    shape.set_edge_style(edge, style);
+                }
+            }
+            if let Some(color) = node.material.color {
+                shape.colors.resize(first, None);
+                shape.colors.resize(shape.vertices.len(), Some(color));
+            }
+        }
+        // nodes after the last one with w or colors
+        if !shape.w.is_empty() {
+            shape.w.resize(shape.vertices.len(), 0.0);
+        }
+        if !shape.colors.is_empty() {
+            shape.colors.resize(shape.vertices.len(), None);
+        }
+    }
+}
+
+impl Display {
+    // Nodes out of view are left out, and ones small on screen drawn with
+    // their lods.
+    pub fn render_scene(&mut self, scene: &Scene) -> &[(f32, u32)] {
+        self.draw_scene(scene);
+        self.fill_frame();
+        self.frame()
+    }
+
+    pub fn draw_scene(&mut self, scene: &Scene) {
+        let mut shape = std::mem::replace(&mut self.scene, Shape::empty());
+        let (objects, culled) = (Cell::new(0), Cell::new(0));
+        scene.flatten_with(&mut shape, |node, transform| {
+            let Some(sphere) = node.bounding_sphere() else {
+                return Some(node.shape());
+            };
+            let (center, radius) = (transform.apply(&sphere.center), sphere.radius * transform.max_scale());
+            if !self.sphere_in_view(&center, radius) {
+                culled.set(culled.get() + 1);
+                return None;
+            }
+            objects.set(objects.get() + 1);
+            Some(node.shape_for(self.projected_radius(&center, radius)))
+        });
+        self.draw(&shape);
+        self.stats.objects = objects.get();
+        self.stats.culled = culled.get();
+        self.scene = shape;
+    }
+
+    // Renders a copy of the shape for every instance, one at a time through
+    // the same buffer instead of combining them into one big shape. Instances
+    // out of view are skipped. The debug overlay isn't drawn for instances.
+    pub fn render_instances(&mut self, model: &Model, instances: &[Instance]) -> &[(f32, u32)] {
+        self.draw_instances(model, instances);
+        self.fill_frame();
+        self.frame()
+    }
+
+    pub fn draw_instances(&mut self, model: &Model, instances: &[Instance]) {
+        self.project_instances(model, instances);
+        self.shade();
+        self.overlay.fill(false);
+    }
+
+    fn project_instances(&mut self, model: &Model, instances: &[Instance]) {
+        let shape = model.shape();
+        let mut vertices = std::mem::take(&mut self.instance);
+        let mut visible = std::mem::take(&mut self.in_view);
+        let place = |vertices: &mut Vec<Point>, instance: &Instance| {
+            vertices.clear();
+            vertices.extend(shape.vertices.iter().map(|v| instance.transform.apply(v)));
+        };
+        let faces = shape.faces.len();
+        let sphere = model.bounding_sphere();
+        let in_view = |instance: &Instance| {
+            sphere.is_none_or(|s| {
+                let transform = &instance.transform;
+                self.sphere_in_view(&transform.apply(&s.center), s.radius * transform.max_scale())
+            })
+        };
+        visible.clear();
+        visible.extend(instances.iter().map(in_view));
+        self.clear(shape);
+        self.stats.objects = visible.iter().filter(|v| **v).count();
+        self.stats.culled = instances.len() - self.stats.objects;
+        let drawn = || instances.iter().enumerate().filter(|(k, _)| visible[*k]);
+        // all faces first, they hide the lines of other instances too
+        if self.needs_faces() {
+            for (k, instance) in drawn() {
+                place(&mut vertices, instance);
+                self.project_faces(&vertices, &shape.faces, k * faces);
+            }
+        }
+        self.find_priorities(shape);
+        for (k, instance) in drawn() {
+            place(&mut vertices, instance);
+            self.project_edges(shape, &vertices, k * faces, instance.color);
+            self.project_vertices(shape, &vertices, k * faces, instance.color);
+        }
+        self.fill_faces(|id| {
+            let (k, f) = (id / faces, id % faces);
+            instances[k].color.or(shape.colors.get(shape.faces[f].0).copied().flatten())
+        });
+        if let Some(outline) = self.outline {
+            self.detect_outlines(outline);
+        }
+        self.instance = vertices;
+        self.in_view = visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shapes::{EdgeStyle, LineStyle, Point, Shape},
+        Display, RGB,
+    };
+
+    use super::{Instance, Lod, Material, Model, Scene, Transform};
+
+    fn close(a: Point, b: Point) -> bool {
+        (a - b).magnitude() < 1e-9
+    }
+
+    #[test]
+    fn transforms() {
+        let mut cube = Shape::generate_cube(Point::zero(), 2.0);
+        let pivot = Point(1.0, 2.0, 3.0);
+        let angles = (0.3, -0.7, 1.1);
+        let rotation = Transform::rotation_about(pivot, angles);
+        let expected: Vec<Point> = cube.vertices.iter().map(|v| rotation.apply(v)).collect();
+        cube.rotate(&pivot, angles);
+        assert!(cube.vertices.iter().zip(expected).all(|(a, b)| close(*a, b)));
+
+        let moved = Transform::scale(2.0).then(&Transform::translation(Point(1.0, 0.0, 0.0)));
+        assert!(close(moved.apply(&Point(1.0, 1.0, 1.0)), Point(3.0, 2.0, 2.0)));
+    }
+
+    #[test]
+    fn hierarchy() {
+        let mut scene = Scene::new();
+        let group = scene.add_group("group", None);
+        scene.node_mut(group).transform = Transform::translation(Point(10.0, 0.0, 0.0));
+        let line = Shape::generate_line(Point::zero(), Point(1.0, 0.0, 0.0));
+        let a = scene.add_child(group, "a", Shape::generate_line(Point::zero(), Point(1.0, 0.0, 0.0)));
+        let b = scene.add("b", line);
+        scene.node_mut(a).transform = Transform::translation(Point(0.0, 5.0, 0.0));
+        scene.node_mut(b).material = Material {
+            color: Some(RGB(1, 2, 3)),
+            edges: Some(EdgeStyle {
+                line: LineStyle::Dotted,
+                priority: 0,
+            }),
+        };
+        assert_eq!(scene.find("b"), Some(b));
+
+        let shape = scene.flatten();
+        assert!(close(shape.vertices[0], Point(10.0, 5.0, 0.0)));
+        // the second line's edge points at its own vertices
+        assert_eq!((shape.edges[1].0, shape.edges[1].1), (2, 3));
+        assert_eq!(shape.edge_style(1).line, LineStyle::Dotted);
+        assert_eq!(shape.colors, [None, None, Some(RGB(1, 2, 3)), Some(RGB(1, 2, 3))]);
+
+        scene.node_mut(group).visible = false;
+        assert!(!scene.is_visible(a));
+        assert_eq!(scene.flatten().vertices.len(), 2);
+    }
+
+    #[test]
+    fn instances() {
+        let mut display = Display::new(60, 30, Point(0.0, -40.0, 0.0), Point::e(2), 20.0);
+        display.set_solid(true);
+        let cube = Model::new(Shape::generate_cube(Point::zero(), 4.0));
+        let instances: Vec<Instance> = (0..3)
+            .map(|k| Instance {
+                transform: Transform::rotation((0.3, 0.5, 0.0))
+                    .then(&Transform::translation(Point((k as f64 - 1.0) * 10.0, 0.0, 0.0))),
+                color: Some(RGB(k * 100, 0, 0)),
+            })
+            .collect();
+        let many = display.render_instances(&cube, &instances).to_vec();
+
+        // the same as drawing the copies combined, with colors as materials
+        let mut scene = Scene::new();
+        for instance in &instances {
+            let node = scene.add("cube", Shape::generate_cube(Point::zero(), 4.0));
+            scene.node_mut(node).transform = instance.transform;
+            scene.node_mut(node).material.color = instance.color;
+        }
+        let cells = display.cells().to_vec();
+        assert_eq!(display.render_scene(&scene), many);
+        assert_eq!(display.cells(), cells);
+        assert!(cells.iter().any(|c| c.fg.is_some_and(|fg| fg.0 > 150)));
+    }
+
+    #[test]
+    fn culling_and_lods() {
+        let mut display = Display::new(40, 20, Point::zero(), Point::e(2), 20.0);
+        let mut scene = Scene::new();
+        let ring = |at: Point| Shape::generate_ring(2.0, at);
+        let near = scene.add("near", ring(Point(0.0, 10.0, 0.0)));
+        let far = scene.add("far", ring(Point(0.0, 200.0, 0.0)));
+        scene.add("behind", ring(Point(0.0, -10.0, 0.0)));
+        for id in [near, far] {
+            scene.node_mut(id).lods = vec![Lod {
+                below: 1.0,
+                shape: Shape::generate_ring_sampled(2.0, scene.node(id).shape().center.unwrap(), 8),
+            }];
+        }
+        display.draw_scene(&scene);
+        let stats = display.stats();
+        assert_eq!((stats.objects, stats.culled), (2, 1));
+        // the far ring is 0.2 cells across, so the 8 point one
+        assert_eq!(stats.vertices, 108);
+        // the kept sphere follows the shape behind the camera
+        *scene.node_mut(near).shape_mut() = ring(Point(0.0, -10.0, 0.0));
+        display.draw_scene(&scene);
+        assert_eq!(display.stats().culled, 2);
+
+        let cube = Model::new(Shape::generate_cube(Point::zero(), 1.0));
+        let instances: Vec<Instance> = (-5..5)
+            .map(|k| Instance {
+                transform: Transform::translation(Point(k as f64 * 10.0, 20.0, 0.0)),
+                color: None,
+            })
+            .collect();
+        display.draw_instances(&cube, &instances);
+        let stats = display.stats();
+        assert_eq!((stats.objects, stats.culled), (5, 5));
+        assert_eq!(stats.edges, 5 * 12);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::RGB;
+
 const VERTEX_DENSITY: usize = 100;
 
 // Really whis is a 3D vector...
@@ -118,7 +120,13 @@ impl Point {
     }
 
     pub fn unit(&self) -> Point {
-        self.clone() / self.magnitude()
+        *self / self.magnitude()
+    }
+
+    // `None` for vectors with no direction, where `unit` gives NaN.
+    pub fn try_unit(&self) -> Option<Point> {
+        let magnitude = self.magnitude();
+        (magnitude > 0.0 && magnitude.is_finite()).then(|| *self / magnitude)
     }
 
     pub fn cross(&self, other: &Self) -> Self {
@@ -136,23 +144,23 @@ impl Point {
         //
         // cos theta -sin theta
         // sin theta cos theta
-        let old_0 = self.0.clone();
-        let old_1 = self.1.clone();
+        let old_0 = self.0;
+        let old_1 = self.1;
         self.0 = theta.cos() * old_0 - theta.sin() * old_1;
         self.1 = theta.sin() * old_0 + theta.cos() * old_1;
-        // let old_0 = self.0.clone();
-        // let old_1 = self.1.clone();
+        // let old_0 = self.0;
+        // let old_1 = self.1;
         // self.0 = phi.cos() * old_0 - phi.sin() * old_1;
         // self.1 = phi.sin() * old_0 + phi.cos() * old_1;
         
         // rotate in c = (-y, x, 0)/r,p = (x,y,0)/r ,k = (0,0,1) basis where coordinates c = 0, p = r, k = z
         let r = (self.0.powf(2.0) + self.1.powf(2.0)).sqrt();
-        let z = self.2.clone();
+        let z = self.2;
         let p_new = phi.cos() * r - phi.sin() * z;
         let k_new = phi.sin() * r + phi.cos() * z;
         // to natural basis
-        let old_0 = self.0.clone();
-        let old_1 = self.1.clone();
+        let old_0 = self.0;
+        let old_1 = self.1;
         // let old_2 = self.2;
         self.0 = (old_0 * p_new) / r;
         self.1 = (old_1 * p_new) / r;
@@ -191,16 +199,54 @@ impl Point4 {
     }
 
     pub fn unit(&self) -> Point4 {
-        self.clone() / self.magnitude()
+        *self / self.magnitude()
     }
 }
 
 // This holds the indexes of two points forming an edge.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Edge(pub usize, pub usize);
 
+impl Edge {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self(start, end)
+    }
+}
+
+// How an edge is drawn, kept per edge in `edge_styles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EdgeStyle {
+    pub line: LineStyle,
+    pub priority: u8,
+}
+
+impl EdgeStyle {
+    pub fn new(line: LineStyle) -> Self {
+        Self { line, priority: 0 }
+    }
+
+    // Where edges cross on screen the one with the higher priority is drawn,
+    // even if it's further away.
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    // drawn with double line glyphs where there is one for the slope
+    Double,
+    // bold
+    Thick,
+}
+
 // This holds the indexes of points forming a face.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Face(pub usize, pub usize, pub usize);
 
 #[derive(Debug)]
@@ -208,6 +254,14 @@ pub struct Shape {
     pub vertices: Vec<Point>,
     pub edges: Vec<Edge>,
     pub faces: Vec<Face>,
+    // 4th coordinate per vertex when projected from 4D, else empty
+    pub w: Vec<f64>,
+    // color per vertex, else empty. Edges take the color of their first
+    // vertex and faces that of their first corner.
+    pub colors: Vec<Option<RGB>>,
+    // style per edge, else empty. Edges past the end are plain solid lines.
+    // Whatever removes or reorders edges has to do the same here.
+    pub edge_styles: Vec<EdgeStyle>,
     pub center: Option<Point>,
 }
 
@@ -215,9 +269,21 @@ pub struct Shape {
 pub struct Shape4 {
     pub vertices: Vec<Point4>,
     pub edges: Vec<Edge>,
+    // style per edge, else empty, as on Shape
+    pub edge_styles: Vec<EdgeStyle>,
+    pub faces: Vec<Face>,
+    // the 3-cells bounding the shape, like the 8 cubes of a tesseract
+    pub facets: Vec<Facet>,
     pub center: Option<Point4>,
 }
 
+// A 3-cell of a 4D shape, by the indexes of its vertices and faces.
+#[derive(Debug, Clone)]
+pub struct Facet {
+    pub vertices: Vec<usize>,
+    pub faces: Vec<usize>,
+}
+
 impl Shape {
     pub fn rotate(&mut self, pivot: &Point, (xrot, yrot, zrot): (f64, f64, f64)) {
         let (sin_xrot, cos_xrot) = xrot.sin_cos();
@@ -225,45 +291,107 @@ impl Shape {
         let (sin_zrot, cos_zrot) = zrot.sin_cos();
         // println!("{}", self.vertices.len());
         for v in &mut self.vertices {
-            let prev = v.clone() - pivot.clone();
-            let mut new = prev.clone();
+            let prev = *v - *pivot;
+            let mut new = prev;
             // y-rotation
             new.0 = prev.0 * cos_yrot + prev.2 * sin_yrot;
             new.2 = -prev.0 * sin_yrot + prev.2 * cos_yrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // x-rotation
             new.1 = prev.1 * cos_xrot - prev.2 * sin_xrot;
             new.2 = prev.1 * sin_xrot + prev.2 * cos_xrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // z-rotation
             new.0 = prev.0 * cos_zrot + prev.1 * sin_zrot;
             new.1 = -prev.0 * sin_zrot + prev.1 * cos_zrot;
-            v.set(new + pivot.clone());
+            v.set(new + *pivot);
         }
     }
-    pub fn combine(&self, s2: &Self) -> Self {
-        let mut vertices = self.vertices.clone();
-        let mut edges = self.edges.clone();
-        let mut faces = self.faces.clone();
-        vertices.append(&mut s2.vertices.clone());
-        edges.append(&mut s2.edges.clone());
-        faces.append(&mut s2.faces.clone());
+    pub fn empty() -> Shape {
         Shape {
-            vertices,
-            edges,
-            faces,
+            vertices: Vec::new(),
+            edges: Vec::new(),
+            faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
             center: None,
         }
     }
 
+    // Empties every buffer but keeps their memory, to refill the shape.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.edges.clear();
+        self.faces.clear();
+        self.w.clear();
+        self.colors.clear();
+        self.edge_styles.clear();
+        self.center = None;
+    }
+
+    pub fn combine(&self, s2: &Self) -> Self {
+        let mut shape = Shape {
+            vertices: self.vertices.clone(),
+            edges: self.edges.clone(),
+            faces: self.faces.clone(),
+            w: self.w.clone(),
+            colors: self.colors.clone(),
+            edge_styles: self.edge_styles.clone(),
+            center: None,
+        };
+        shape.append(s2);
+        shape.center = shape.centroid();
+        shape
+    }
+
+    // Adds the vertices, edges and faces of another shape, with its indexes
+    // moved past the vertices already there.
+    pub fn append(&mut self, other: &Shape) {
+        let offset = self.vertices.len();
+        self.vertices.extend_from_slice(&other.vertices);
+        let edge_offset = self.edges.len();
+        self.edges
+            .extend(other.edges.iter().map(|e| Edge(e.0 + offset, e.1 + offset)));
+        self.faces
+            .extend(other.faces.iter().map(|f| Face(f.0 + offset, f.1 + offset, f.2 + offset)));
+        // a shape without w is at w = 0, and without colors uncolored
+        if !self.w.is_empty() || !other.w.is_empty() {
+            self.w.resize(offset, 0.0);
+            self.w.extend(other.w.iter().copied().chain(std::iter::repeat(0.0)).take(other.vertices.len()));
+        }
+        if !self.colors.is_empty() || !other.colors.is_empty() {
+            self.colors.resize(offset, None);
+            self.colors.extend(other.colors.iter().copied().chain(std::iter::repeat(None)).take(other.vertices.len()));
+        }
+        if !self.edge_styles.is_empty() || !other.edge_styles.is_empty() {
+            self.edge_styles.resize(edge_offset, EdgeStyle::default());
+            self.edge_styles.extend(other.edge_styles.iter().copied().chain(std::iter::repeat(EdgeStyle::default())).take(other.edges.len()));
+        }
+    }
+
+    pub fn edge_style(&self, edge: usize) -> EdgeStyle {
+        self.edge_styles.get(edge).copied().unwrap_or_default()
+    }
+
+    pub fn set_edge_style(&mut self, edge: usize, style: EdgeStyle) {
+        if self.edge_styles.len() <= edge {
+            self.edge_styles.resize(self.edges.len().max(edge + 1), EdgeStyle::default());
+        }
+        self.edge_styles[edge] = style;
+    }
+
     pub fn generate_ring(radius: f64, center: Point) -> Shape {
-        let mut vertices: Vec<Point> = Vec::new();
+        Self::generate_ring_sampled(radius, center, VERTEX_DENSITY)
+    }
+
+    // A ring of `samples` points, fewer for a lower level of detail.
+    pub fn generate_ring_sampled(radius: f64, center: Point, samples: usize) -> Shape {
+        let mut vertices: Vec<Point> = Vec::with_capacity(samples);
 
-        for c in 0..VERTEX_DENSITY {
+        for c in 0..samples {
             // < rcos(t), rsin(t), 0.0 >
-            let (s, c) = (2.0 * 3.14 / VERTEX_DENSITY as f64 * c as f64).sin_cos();
+            let (s, c) = (2.0 * std::f64::consts::PI / samples as f64 * c as f64).sin_cos();
             let point = Point(radius * s + center.0, radius * c + center.1, 0.0 + center.2);
             vertices.push(point);
         }
@@ -271,6 +399,9 @@ impl Shape {
             vertices,
             edges: Vec::new(),
             faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
             center: Some(center),
         }
     }
@@ -278,8 +409,11 @@ impl Shape {
     pub fn generate_line(start: Point, end: Point) -> Shape {
         Shape {
             vertices: vec![start, end],
-            edges: vec![Edge(0, 1)],
+            edges: vec![Edge::new(0, 1)],
             faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
             center: None,
         }
     }
@@ -291,36 +425,53 @@ impl Shape {
 
         let half = length / 2.0;
         // bottom
-        vertices.push(center.clone() + Point(-half, -half, -half)); // 0
-        vertices.push(center.clone() + Point(-half, -half, half)); // 1
-        vertices.push(center.clone() + Point(half, -half, half)); // 2
-        vertices.push(center.clone() + Point(half, -half, -half)); // 3
-        edges.push(Edge(0, 1));
-        edges.push(Edge(1, 2));
-        edges.push(Edge(2, 3));
-        edges.push(Edge(0, 3));
+        vertices.push(center + Point(-half, -half, -half)); // 0
+        vertices.push(center + Point(-half, -half, half)); // 1
+        vertices.push(center + Point(half, -half, half)); // 2
+        vertices.push(center + Point(half, -half, -half)); // 3
+        edges.push(Edge::new(0, 1));
+        edges.push(Edge::new(1, 2));
+        edges.push(Edge::new(2, 3));
+        edges.push(Edge::new(0, 3));
         // top
-        vertices.push(center.clone() + Point(-half, half, -half)); // 4
-        vertices.push(center.clone() + Point(-half, half, half)); // 5
-        vertices.push(center.clone() + Point(half, half, half)); // 6
-        vertices.push(center.clone() + Point(half, half, -half)); // 7
-        edges.push(Edge(4, 5));
-        edges.push(Edge(5, 6));
-        edges.push(Edge(6, 7));
-        edges.push(Edge(4, 7));
+        vertices.push(center + Point(-half, half, -half)); // 4
+        vertices.push(center + Point(-half, half, half)); // 5
+        vertices.push(center + Point(half, half, half)); // 6
+        vertices.push(center + Point(half, half, -half)); // 7
+        edges.push(Edge::new(4, 5));
+        edges.push(Edge::new(5, 6));
+        edges.push(Edge::new(6, 7));
+        edges.push(Edge::new(4, 7));
         // left
-        edges.push(Edge(0, 4));
-        edges.push(Edge(1, 5));
+        edges.push(Edge::new(0, 4));
+        edges.push(Edge::new(1, 5));
         // right
-        edges.push(Edge(2, 6));
-        edges.push(Edge(3, 7));
-
-        faces.push(Face(0, 1, 2));
+        edges.push(Edge::new(2, 6));
+        edges.push(Edge::new(3, 7));
+
+        // two triangles per side, counter-clockwise seen from outside
+        faces.extend([
+            Face(0, 3, 2),
+            Face(0, 2, 1),
+            Face(4, 6, 7),
+            Face(4, 5, 6),
+            Face(0, 1, 5),
+            Face(0, 5, 4),
+            Face(3, 7, 6),
+            Face(3, 6, 2),
+            Face(0, 4, 7),
+            Face(0, 7, 3),
+            Face(1, 2, 6),
+            Face(1, 6, 5),
+        ]);
 
         Shape {
             vertices,
             edges,
             faces,
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
             center: Some(center),
         }
     }
@@ -339,10 +490,24 @@ impl Shape {
             vertices.push(Point(0.0, 0.0, -(z as f64)));
             vertices.push(Point(0.0, 0.0, z as f64));
         }
+        // dotted axes between the outermost points
+        let edges = match length {
+            0 => Vec::new(),
+            _ => (0..3)
+                .map(|axis| {
+                    let last = (axis * length + length - 1) * 2;
+                    Edge::new(last, last + 1)
+                })
+                .collect(),
+        };
+        let edge_styles = vec![EdgeStyle::new(LineStyle::Dotted); edges.len()];
         Shape {
             vertices,
-            edges: Vec::new(),
+            edges,
             faces: Vec::new(),
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles,
             center: None,
         }
     }
@@ -352,38 +517,68 @@ impl Shape {
         let mut edges: Vec<Edge> = Vec::new();
         vertices.push(start); // 0
         vertices.push(start + a); // 1
-        edges.push(Edge(0, 1));
+        edges.push(Edge::new(0, 1));
 
         vertices.push(start + c); // 2
         vertices.push(start + c + a); // 3
-        edges.push(Edge(2, 3));
-        edges.push(Edge(0, 2));
-        edges.push(Edge(1, 3));
+        edges.push(Edge::new(2, 3));
+        edges.push(Edge::new(0, 2));
+        edges.push(Edge::new(1, 3));
 
         vertices.push(start + b); // 4
         vertices.push(start + b + a); // 5
-        edges.push(Edge(4, 5));
+        edges.push(Edge::new(4, 5));
 
         vertices.push(start + b + c); // 6
         vertices.push(start + b + c + a); // 7
-        edges.push(Edge(6, 7));
-        edges.push(Edge(4, 6));
-        edges.push(Edge(5, 7));
-
-        edges.push(Edge(0, 4));
-        edges.push(Edge(1, 5));
-        edges.push(Edge(2, 6));
-        edges.push(Edge(3, 7));
+        edges.push(Edge::new(6, 7));
+        edges.push(Edge::new(4, 6));
+        edges.push(Edge::new(5, 7));
+
+        edges.push(Edge::new(0, 4));
+        edges.push(Edge::new(1, 5));
+        edges.push(Edge::new(2, 6));
+        edges.push(Edge::new(3, 7));
+
+        // counter-clockwise seen from outside when a, b, c are right handed
+        let flip = a.cross(&b).dot(&c) < 0.0;
+        let faces = [
+            (0, 1, 3),
+            (0, 3, 2),
+            (4, 7, 5),
+            (4, 6, 7),
+            (0, 2, 6),
+            (0, 6, 4),
+            (1, 5, 7),
+            (1, 7, 3),
+            (0, 4, 5),
+            (0, 5, 1),
+            (2, 3, 7),
+            (2, 7, 6),
+        ]
+        .map(|(i, j, k)| if flip { Face(i, k, j) } else { Face(i, j, k) })
+        .to_vec();
 
         Shape {
             vertices,
             edges,
-            faces: Vec::new(),
-            center: Some(start),
+            faces,
+            w: Vec::new(),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
+            center: Some(start + (a + b + c) / 2.0),
         }
     }
 }
 
+#[cfg(test)]
+impl Shape {
+    // vertices, edges and faces, for the tests of the generators
+    pub(crate) fn counts(&self) -> (usize, usize, usize) {
+        (self.vertices.len(), self.edges.len(), self.faces.len())
+    }
+}
+
 impl Shape4 {
     pub fn generate_4d_paralellepiped(
         start: Point4,
@@ -400,10 +595,10 @@ impl Shape4 {
         vertices.push(start + b); // 2
         vertices.push(start + c); // 3
         vertices.push(start + d); // 4
-        edges.push(Edge(0, 1));
-        edges.push(Edge(0, 2));
-        edges.push(Edge(0, 3));
-        edges.push(Edge(0, 4));
+        edges.push(Edge::new(0, 1));
+        edges.push(Edge::new(0, 2));
+        edges.push(Edge::new(0, 3));
+        edges.push(Edge::new(0, 4));
 
         vertices.push(start + a + d); // 5
         vertices.push(start + a + b); // 6
@@ -411,49 +606,190 @@ impl Shape4 {
         vertices.push(start + b + c); // 8
         vertices.push(start + b + d); // 9
         vertices.push(start + c + d); // 10
-        edges.push(Edge(1, 5)); // a to a + d
-        edges.push(Edge(1, 6)); // a to a + b
-        edges.push(Edge(1, 7)); // a to a + c
-        edges.push(Edge(2, 6)); // b to a + b
-        edges.push(Edge(2, 8)); // b to b + c
-        edges.push(Edge(2, 9)); // b to b + d
-        edges.push(Edge(3, 7)); // c to a + c
-        edges.push(Edge(3, 8)); // c to b + c
-        edges.push(Edge(3, 10)); // c to c + d
-        edges.push(Edge(4, 5)); // d to a + d
-        edges.push(Edge(4, 9)); // d to b + d
-        edges.push(Edge(4, 10)); // d to c + d
+        edges.push(Edge::new(1, 5)); // a to a + d
+        edges.push(Edge::new(1, 6)); // a to a + b
+        edges.push(Edge::new(1, 7)); // a to a + c
+        edges.push(Edge::new(2, 6)); // b to a + b
+        edges.push(Edge::new(2, 8)); // b to b + c
+        edges.push(Edge::new(2, 9)); // b to b + d
+        edges.push(Edge::new(3, 7)); // c to a + c
+        edges.push(Edge::new(3, 8)); // c to b + c
+        edges.push(Edge::new(3, 10)); // c to c + d
+        edges.push(Edge::new(4, 5)); // d to a + d
+        edges.push(Edge::new(4, 9)); // d to b + d
+        edges.push(Edge::new(4, 10)); // d to c + d
 
         vertices.push(start + a + b + c); // 11
         vertices.push(start + a + b + d); // 12
         vertices.push(start + a + c + d); // 13
         vertices.push(start + b + c + d); // 14
-        edges.push(Edge(5, 12)); // a + d to a + b + d
-        edges.push(Edge(5, 13)); // a + d to a + c + d
-        edges.push(Edge(6, 11)); // a + b to a + b + c
-        edges.push(Edge(6, 12)); // a + b to a + b + d
-        edges.push(Edge(7, 11)); // a + c to a + b + c
-        edges.push(Edge(7, 13)); // a + c to a + c + d
-        edges.push(Edge(8, 11)); // b + c to a + b + c
-        edges.push(Edge(8, 14)); // b + c to b + c + d
-        edges.push(Edge(9, 12)); // b + d to a + b + d
-        edges.push(Edge(9, 14)); // b + d to b + c + d
-        edges.push(Edge(10, 13)); // c + d to a + c + d
-        edges.push(Edge(10, 14)); // c + d to b + c + d
+        edges.push(Edge::new(5, 12)); // a + d to a + b + d
+        edges.push(Edge::new(5, 13)); // a + d to a + c + d
+        edges.push(Edge::new(6, 11)); // a + b to a + b + c
+        edges.push(Edge::new(6, 12)); // a + b to a + b + d
+        edges.push(Edge::new(7, 11)); // a + c to a + b + c
+        edges.push(Edge::new(7, 13)); // a + c to a + c + d
+        edges.push(Edge::new(8, 11)); // b + c to a + b + c
+        edges.push(Edge::new(8, 14)); // b + c to b + c + d
+        edges.push(Edge::new(9, 12)); // b + d to a + b + d
+        edges.push(Edge::new(9, 14)); // b + d to b + c + d
+        edges.push(Edge::new(10, 13)); // c + d to a + c + d
+        edges.push(Edge::new(10, 14)); // c + d to b + c + d
 
         vertices.push(start + a + b + c + d); // 15
-        edges.push(Edge(11,15)); // a + b + c to a + b + c + d
-        edges.push(Edge(12,15)); // a + b + d to a + b + c + d
-        edges.push(Edge(13,15)); // a + c + d to a + b + c + d
-        edges.push(Edge(14,15)); // b + c + d to a + b + c + d
+        edges.push(Edge::new(11, 15)); // a + b + c to a + b + c + d
+        edges.push(Edge::new(12, 15)); // a + b + d to a + b + c + d
+        edges.push(Edge::new(13, 15)); // a + c + d to a + b + c + d
+        edges.push(Edge::new(14, 15)); // b + c + d to a + b + c + d
+
+        // which of a, b, c, d make up each vertex, as bits
+        const MASKS: [usize; 16] = [0, 1, 2, 4, 8, 9, 3, 5, 6, 10, 12, 7, 11, 13, 14, 15];
+        let index = |mask: usize| MASKS.iter().position(|m| *m == mask).unwrap();
+
+        // edges along d are dashed
+        let edge_styles = edges
+            .iter()
+            .map(|e| match MASKS[e.0] ^ MASKS[e.1] {
+                8 => EdgeStyle::new(LineStyle::Dashed),
+                _ => EdgeStyle::default(),
+            })
+            .collect();
+
+        // every square spanned by two of the directions, as two triangles
+        let mut faces = Vec::new();
+        let mut squares = Vec::new();
+        for i in 0..4 {
+            for j in i + 1..4 {
+                let (i, j) = (1 << i, 1 << j);
+                for base in (0..16).filter(|m| m & (i | j) == 0) {
+                    let [p, q, r, s] = [base, base | i, base | i | j, base | j].map(index);
+                    squares.push((i | j, base, faces.len()));
+                    faces.push(Face(p, q, r));
+                    faces.push(Face(p, r, s));
+                }
+            }
+        }
+        // a cube on each side of the shape in each direction
+        let mut facets = Vec::new();
+        for k in (0..4).map(|k| 1 << k) {
+            for side in [0, k] {
+                facets.push(Facet {
+                    vertices: (0..16).filter(|m| m & k == side).map(index).collect(),
+                    faces: squares
+                        .iter()
+                        .filter(|(axes, base, _)| axes & k == 0 && base & k == side)
+                        .flat_map(|(_, _, f)| [*f, f + 1])
+                        .collect(),
+                });
+            }
+        }
 
         Shape4 {
             vertices,
             edges,
-            center: None,
+            edge_styles,
+            faces,
+            facets,
+            center: Some(start + (a + b + c + d) / 2.0),
         }
     }
 
+    // The 4D simplex, 5 vertices all the same distance apart.
+    pub fn generate_5_cell(center: Point4, radius: f64) -> Shape4 {
+        let s = 1.0 / 5f64.sqrt();
+        // all at a distance of sqrt(16/5) from the origin
+        let scale = radius / (16.0f64 / 5.0).sqrt();
+        let vertices = [
+            Point4(1.0, 1.0, 1.0, -s),
+            Point4(1.0, -1.0, -1.0, -s),
+            Point4(-1.0, 1.0, -1.0, -s),
+            Point4(-1.0, -1.0, 1.0, -s),
+            Point4(0.0, 0.0, 0.0, 4.0 * s),
+        ]
+        .map(|v| center + v * scale)
+        .to_vec();
+        // every 4 of the vertices form a tetrahedron
+        let cells = (0..5)
+            .map(|skip| {
+                let mut cell = [0; 4];
+                for (k, v) in (0..5).filter(|v| *v != skip).enumerate() {
+                    cell[k] = v;
+                }
+                cell
+            })
+            .collect();
+        Self::from_tetrahedra(vertices, cells, center)
+    }
+
+    // The 4D cross-polytope, a vertex on each side of each axis.
+    pub fn generate_16_cell(center: Point4, radius: f64) -> Shape4 {
+        let mut vertices = Vec::new();
+        for axis in 1..=4 {
+            vertices.push(center + Point4::e(axis) * radius); // 2 * (axis - 1)
+            vertices.push(center + Point4::e(axis) * -radius); // 2 * (axis - 1) + 1
+        }
+        // one tetrahedron per choice of side on all four axes
+        let cells = (0..16)
+            .map(|sides: usize| [0, 1, 2, 3].map(|axis| 2 * axis + (sides >> axis & 1)))
+            .collect();
+        Self::from_tetrahedra(vertices, cells, center)
+    }
+
+    // Edges, faces and facets of a shape made of tetrahedra.
+    fn from_tetrahedra(vertices: Vec<Point4>, cells: Vec<[usize; 4]>, center: Point4) -> Shape4 {
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut faces: Vec<Face> = Vec::new();
+        let mut facets = Vec::new();
+        for cell in cells {
+            let [a, b, c, d] = cell;
+            for (p, q) in [(a, b), (a, c), (a, d), (b, c), (b, d), (c, d)] {
+                if !edges.iter().any(|e| (e.0, e.1) == (p, q) || (e.0, e.1) == (q, p)) {
+                    edges.push(Edge::new(p, q));
+                }
+            }
+            let mut cell_faces = Vec::new();
+            for triangle in [[a, b, c], [a, b, d], [a, c, d], [b, c, d]] {
+                let same = |f: &Face| triangle.iter().all(|v| [f.0, f.1, f.2].contains(v));
+                let index = match faces.iter().position(same) {
+                    Some(index) => index,
+                    None => {
+                        faces.push(Face(triangle[0], triangle[1], triangle[2]));
+                        faces.len() - 1
+                    }
+                };
+                cell_faces.push(index);
+            }
+            facets.push(Facet {
+                vertices: cell.to_vec(),
+                faces: cell_faces,
+            });
+        }
+        Shape4 {
+            vertices,
+            edges,
+            edge_styles: Vec::new(),
+            faces,
+            facets,
+            center: Some(center),
+        }
+    }
+
+    // Picks out one facet in a shape projected from this one: its edges are
+    // drawn thick over the others, and only its faces are kept.
+    pub fn highlight_facet(&self, shape: &mut Shape, facet: usize) {
+        let Some(facet) = self.facets.get(facet) else {
+            return;
+        };
+        for e in 0..shape.edges.len() {
+            let Edge(start, end) = shape.edges[e];
+            if facet.vertices.contains(&start) && facet.vertices.contains(&end) {
+                shape.set_edge_style(e, EdgeStyle::new(LineStyle::Thick).with_priority(1));
+            }
+        }
+        shape.faces.clear();
+        shape.faces.extend(facet.faces.iter().map(|f| self.faces[*f].clone()));
+    }
+
     pub fn rotate(
         &mut self,
         pivot: &Point4,
@@ -467,52 +803,141 @@ impl Shape4 {
         let (sin_wzrot, cos_wzrot) = wzrot.sin_cos();
         // println!("{}", self.vertices.len());
         for v in &mut self.vertices {
-            let prev = v.clone() - pivot.clone();
-            let mut new = prev.clone();
+            let prev = *v - *pivot;
+            let mut new = prev;
             // xz-rotation
             new.0 = prev.0 * cos_xzrot + prev.2 * sin_xzrot;
             new.2 = -prev.0 * sin_xzrot + prev.2 * cos_xzrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // yz-rotation
             new.1 = prev.1 * cos_yzrot - prev.2 * sin_yzrot;
             new.2 = prev.1 * sin_yzrot + prev.2 * cos_yzrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // xy-rotation
             new.0 = prev.0 * cos_xyrot + prev.1 * sin_xyrot;
             new.1 = -prev.0 * sin_xyrot + prev.1 * cos_xyrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // wx-rotation
             new.0 = prev.0 * cos_wxrot + prev.3 * sin_wxrot;
             new.3 = -prev.0 * sin_wxrot + prev.3 * cos_wxrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // wy-rotation
             new.1 = prev.1 * cos_wyrot + prev.3 * sin_wyrot;
             new.3 = -prev.1 * sin_wyrot + prev.3 * cos_wyrot;
-            let prev = new.clone();
-            let mut new = prev.clone();
+            let prev = new;
             // wz-rotation
             new.2 = prev.2 * cos_wzrot + prev.3 * sin_wzrot;
             new.3 = -prev.2 * sin_wzrot + prev.3 * cos_wzrot;
-            v.set(new + pivot.clone());
+            v.set(new + *pivot);
         }
     }
 
     pub fn project_to_3d(&self) -> Shape {
-        let mut vertices: Vec<Point> = Vec::new();
+        let mut shape = Shape {
+            vertices: Vec::with_capacity(self.vertices.len()),
+            edges: Vec::with_capacity(self.edges.len()),
+            faces: Vec::with_capacity(self.faces.len()),
+            w: Vec::with_capacity(self.vertices.len()),
+            colors: Vec::new(),
+            edge_styles: Vec::new(),
+            center: None,
+        };
+        self.project_to_3d_into(&mut shape);
+        shape
+    }
+
+    // Same as project_to_3d but reuses the buffers of an existing shape,
+    // so projecting every frame doesn't allocate.
+    pub fn project_to_3d_into(&self, shape: &mut Shape) {
+        shape.vertices.clear();
+        shape
+            .vertices
+            .extend(self.vertices.iter().map(|v| Point(v.0, v.1, v.2)));
+        shape.edges.clone_from(&self.edges);
+        shape.edge_styles.clone_from(&self.edge_styles);
+        shape.faces.clone_from(&self.faces);
+        shape.w.clear();
+        shape.w.extend(self.vertices.iter().map(|v| v.3));
+        shape.colors.clear();
+        shape.center = self.center.map(|c| Point(c.0, c.1, c.2));
+    }
+}
 
-        for v in &self.vertices {
-            vertices.push(Point(v.0, v.1, v.2))
+#[cfg(test)]
+mod tests {
+    use super::{EdgeStyle, LineStyle, Point, Point4, Shape, Shape4};
+
+    // vertices, edges, faces and facets
+    fn counts(shape: &Shape4) -> (usize, usize, usize, usize) {
+        (shape.vertices.len(), shape.edges.len(), shape.faces.len(), shape.facets.len())
+    }
+
+    #[test]
+    fn combine() {
+        let cube = Shape::generate_cube(Point::zero(), 2.0);
+        let line = Shape::generate_line(Point::zero(), Point::e(1));
+        let both = line.combine(&cube);
+        assert_eq!(both.vertices.len(), 10);
+        assert_eq!(both.faces.len(), cube.faces.len());
+        // the cube's indices moved past the line's vertices
+        assert!(both.edges[1..].iter().all(|e| e.0 >= 2 && e.1 >= 2));
+        assert!(both.faces.iter().all(|f| f.0 >= 2 && f.1 >= 2 && f.2 >= 2));
+
+        // styles stay with their edges, the cube's plain
+        let mut grid = Shape::generate_grid(2);
+        grid.append(&cube);
+        assert_eq!(grid.edge_styles.len(), 3 + 12);
+        assert_eq!(grid.edge_style(2).line, LineStyle::Dotted);
+        assert_eq!(grid.edge_style(3), EdgeStyle::default());
+        let mut line = line.combine(&grid);
+        assert_eq!(line.edge_style(0), EdgeStyle::default());
+        assert_eq!(line.edge_style(1).line, LineStyle::Dotted);
+        line.set_edge_style(0, EdgeStyle::new(LineStyle::Thick));
+        assert_eq!(line.edge_style(0).line, LineStyle::Thick);
+
+        // nothing left over to line up with a new shape's edges
+        grid.clear();
+        grid.append(&cube);
+        assert!(grid.edge_styles.is_empty());
+    }
+
+    #[test]
+    fn polytopes() {
+        let tesseract = Shape4::generate_4d_paralellepiped(
+            Point4::zero(),
+            Point4::e(1),
+            Point4::e(2),
+            Point4::e(3),
+            Point4::e(4),
+        );
+        // 24 squares
+        assert_eq!(counts(&tesseract), (16, 32, 48, 8));
+        for facet in &tesseract.facets {
+            assert_eq!((facet.vertices.len(), facet.faces.len()), (8, 12));
+            // every face is made of the facet's vertices
+            for f in &facet.faces {
+                let face = &tesseract.faces[*f];
+                assert!([face.0, face.1, face.2].iter().all(|v| facet.vertices.contains(v)));
+            }
         }
-
-        Shape {
-            vertices,
-            edges: self.edges.clone(),
-            faces: Vec::new(),
-            center: None,
+        // opposite cubes share nothing, neighbours share a square
+        let shared = |a: usize, b: usize| {
+            tesseract.facets[a].faces.iter().filter(|f| tesseract.facets[b].faces.contains(f)).count()
+        };
+        assert_eq!(shared(0, 1), 0);
+        assert_eq!(shared(0, 2), 2);
+        // the 8 edges along w are dashed
+        let dashed = tesseract.edges.iter().enumerate().filter(|(e, _)| tesseract.edge_styles[*e].line == LineStyle::Dashed);
+        assert!(dashed.clone().all(|(_, edge)| (tesseract.vertices[edge.1] - tesseract.vertices[edge.0]).3 == 1.0));
+        assert_eq!(dashed.count(), 8);
+
+        assert_eq!(counts(&Shape4::generate_5_cell(Point4::zero(), 1.0)), (5, 10, 10, 5));
+        let cross = Shape4::generate_16_cell(Point4::zero(), 1.0);
+        assert_eq!(counts(&cross), (8, 24, 32, 16));
+        // all edges are the same length
+        for edge in &cross.edges {
+            let length = (cross.vertices[edge.0] - cross.vertices[edge.1]).magnitude();
+            assert!((length - 2f64.sqrt()).abs() < 1e-9);
         }
     }
 }
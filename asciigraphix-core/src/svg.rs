@@ -0,0 +1,249 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    shapes::{LineStyle, Shape},
+    Display, HiddenLines, Triangle, RGB,
+};
+
+// How edges look in an SVG. Widths and opacities go from the nearest to the
+// farthest edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    pub stroke: RGB,
+    pub background: Option<RGB>,
+    pub width: (f64, f64),
+    pub opacity: (f64, f64),
+    pub hidden: HiddenLines,
+    // pixels per display cell
+    pub scale: f64,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            stroke: RGB(0, 0, 0),
+            background: Some(RGB(255, 255, 255)),
+            width: (2.5, 0.75),
+            opacity: (1.0, 0.35),
+            hidden: HiddenLines::Dashed,
+            scale: 8.0,
+        }
+    }
+}
+
+// Part of an edge drawn as one SVG line.
+#[derive(Clone, Copy)]
+struct Line {
+    start: (f64, f64),
+    end: (f64, f64),
+    hidden: bool,
+    // stroke width and opacity
+    looks: (f64, f64),
+    line: LineStyle,
+}
+
+impl Display {
+    // The projected edges as vector lines, using the same camera as the other
+    // renderers but without rasterizing. Coordinates are display cells times
+    // `style.scale`, so unlike the terminal, cells are square here.
+    pub fn write_svg<W: Write>(&self, shape: &Shape, style: &SvgStyle, out: &mut W) -> io::Result<()> {
+        // anything closer is clipped, to keep lines from going off to infinity
+        const NEAR: f64 = 1e-3;
+        // pieces each edge is cut into, for hidden parts and changing depth
+        const PIECES: usize = 32;
+
+        let view: Vec<_> = shape.vertices.iter().map(|v| self.view(v)).collect();
+        let triangles: Vec<Triangle> = shape
+            .faces
+            .iter()
+            .filter_map(|f| self.triangle(&shape.vertices, f))
+            .collect();
+
+        let clipped: Vec<_> = shape
+            .edges
+            .iter()
+            .enumerate()
+            .filter_map(|(k, e)| {
+                let (mut a, mut b) = (view[e.0], view[e.1]);
+                if a.2 <= NEAR && b.2 <= NEAR {
+                    return None;
+                }
+                let lerp = |p: (f64, f64, f64), q: (f64, f64, f64), t: f64| {
+                    (p.0 + (q.0 - p.0) * t, p.1 + (q.1 - p.1) * t, p.2 + (q.2 - p.2) * t)
+                };
+                if a.2 <= NEAR {
+                    a = lerp(a, b, (NEAR - a.2) / (b.2 - a.2));
+                } else if b.2 <= NEAR {
+                    b = lerp(b, a, (NEAR - b.2) / (a.2 - b.2));
+                }
+                Some(((e.0, e.1, shape.edge_style(k).line), a, b))
+            })
+            .collect();
+
+        let (near, far) = clipped
+            .iter()
+            .flat_map(|(_, a, b)| [a.2, b.2])
+            .fold((f64::MAX, f64::MIN), |(n, f), d| (n.min(d), f.max(d)));
+        let looks = |depth: f64, hidden: bool| {
+            let t = if far > near { (depth - near) / (far - near) } else { 0.0 };
+            let width = style.width.0 + (style.width.1 - style.width.0) * t;
+            let opacity = style.opacity.0 + (style.opacity.1 - style.opacity.0) * t;
+            let opacity = if hidden && style.hidden == HiddenLines::Dashed {
+                opacity / 2.0
+            } else {
+                opacity
+            };
+            // in steps, so that neighbouring pieces can be merged
+            ((width * 10.0).round() / 10.0, (opacity * 20.0).round() / 20.0)
+        };
+
+        let (width, height) = (self.x_size as f64 * style.scale, self.y_size as f64 * style.scale);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+            width, height, width, height
+        );
+        if let Some(bg) = style.background {
+            let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(bg));
+        }
+        let _ = writeln!(
+            svg,
+            "<g stroke=\"{}\" stroke-linecap=\"round\" fill=\"none\">",
+            hex(style.stroke)
+        );
+
+        for ((i, j, line_style), a, b) in clipped {
+            let at = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t);
+            let mut line: Option<Line> = None;
+            for piece in 0..PIECES {
+                let (t0, t1) = (piece as f64 / PIECES as f64, (piece + 1) as f64 / PIECES as f64);
+                let mid = at((t0 + t1) / 2.0);
+                let mid_screen = self.screen(mid);
+                let hidden = triangles.iter().any(|tri| {
+                    !(tri.vertices.contains(&i) && tri.vertices.contains(&j))
+                        && tri.depth_at(mid_screen).is_some_and(|d| d < mid.2 * (1.0 - 1e-6))
+                });
+                let piece = Line {
+                    start: self.screen(at(t0)),
+                    end: self.screen(at(t1)),
+                    hidden,
+                    looks: looks(mid.2, hidden),
+                    line: line_style,
+                };
+                line = match line {
+                    Some(l) if l.hidden == piece.hidden && l.looks == piece.looks => Some(Line {
+                        end: piece.end,
+                        ..l
+                    }),
+                    Some(previous) => {
+                        push_line(&mut svg, previous, style);
+                        Some(piece)
+                    }
+                    None => Some(piece),
+                };
+            }
+            if let Some(last) = line {
+                push_line(&mut svg, last, style);
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+        out.write_all(svg.as_bytes())
+    }
+
+    pub fn save_svg<P: AsRef<Path>>(&self, shape: &Shape, style: &SvgStyle, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_svg(shape, style, &mut out)?;
+        out.flush()
+    }
+}
+
+fn push_line(svg: &mut String, line: Line, style: &SvgStyle) {
+    let dash = match (line.hidden, style.hidden, line.line) {
+        (true, HiddenLines::Hide, _) => return,
+        (true, HiddenLines::Dashed, _) => " stroke-dasharray=\"4 3\"",
+        (_, _, LineStyle::Dashed) => " stroke-dasharray=\"8 4\"",
+        // round caps turn the empty dashes into dots
+        (_, _, LineStyle::Dotted) => " stroke-dasharray=\"0 4\"",
+        _ => "",
+    };
+    let (width, opacity) = line.looks;
+    let s = style.scale;
+    let (start, end) = ((line.start.0 * s, line.start.1 * s), (line.end.0 * s, line.end.1 * s));
+    let mut push = |offset: (f64, f64), width: f64| {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke-width=\"{}\" opacity=\"{}\"{}/>",
+            start.0 + offset.0,
+            start.1 + offset.1,
+            end.0 + offset.0,
+            end.1 + offset.1,
+            width,
+            opacity,
+            dash
+        );
+    };
+    match line.line {
+        LineStyle::Thick => push((0.0, 0.0), width * 2.0),
+        // two lines a width apart on both sides
+        LineStyle::Double => {
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = (dx * dx + dy * dy).sqrt().max(1e-9);
+            let normal = (-dy / length * width, dx / length * width);
+            push(normal, width / 2.0);
+            push((-normal.0, -normal.1), width / 2.0);
+        }
+        _ => push((0.0, 0.0), width),
+    }
+}
+
+fn hex(c: RGB) -> String {
+    format!("#{:06x}", c.to_u32())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shapes::{Point, Shape},
+        Display, HiddenLines,
+    };
+
+    use super::SvgStyle;
+
+    fn svg(shape: &Shape, hidden: HiddenLines) -> String {
+        let display = Display::new(40, 40, Point(3.0, -30.0, 4.0), Point(0.0, 1.0, 0.0), 40.0);
+        let style = SvgStyle {
+            hidden,
+            ..SvgStyle::default()
+        };
+        let mut out = Vec::new();
+        display.write_svg(shape, &style, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn hidden_lines() {
+        let cube = Shape::generate_cube(Point(0.0, 0.0, 0.0), 10.0);
+        let dashed = svg(&cube, HiddenLines::Dashed);
+        assert!(dashed.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"320\""));
+        assert!(dashed.ends_with("</g>\n</svg>\n"));
+        assert!(dashed.contains("stroke-dasharray"));
+        let hidden = svg(&cube, HiddenLines::Hide);
+        assert!(!hidden.contains("stroke-dasharray"));
+        assert!(hidden.matches("<line").count() < dashed.matches("<line").count());
+    }
+
+    #[test]
+    fn no_faces_nothing_hidden() {
+        let line = Shape::generate_line(Point(-5.0, 0.0, 0.0), Point(5.0, 0.0, 0.0));
+        let out = svg(&line, HiddenLines::Dashed);
+        assert!(!out.contains("stroke-dasharray"));
+        // same depth everywhere, one piece
+        assert_eq!(out.matches("<line").count(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::shapes::{Edge, Face, Facet, Point4, Shape4};
+
+// A turn of a cube about the face it shares with its neighbour, in the plane
+// of one of x, y, z and w.
+#[derive(Clone, Copy)]
+struct Fold {
+    axis: usize,
+    // where the hinge is along `axis` and w
+    hinge: (f64, f64),
+    // which way along `axis` the cube's w side ends up
+    direction: f64,
+}
+
+impl Fold {
+    fn apply(&self, p: [f64; 4], angle: f64) -> [f64; 4] {
+        let (sin, cos) = angle.sin_cos();
+        let (u, v) = (p[self.axis] - self.hinge.0, p[3] - self.hinge.1);
+        let mut q = p;
+        q[self.axis] = self.hinge.0 + u * cos + self.direction * v * sin;
+        q[3] = self.hinge.1 + v * cos - self.direction * u * sin;
+        q
+    }
+}
+
+impl Shape4 {
+    // A tesseract of side `size` around `center` at t = 0, unfolded into its
+    // 3D net, the Dalí cross, at t = 1. The cube at the low end of w stays,
+    // the six around it fold out over their shared faces and the opposite
+    // cube follows the one at the low end of z. The net ends up in the
+    // hyperplane of the cube that stays.
+    //
+    // Every cube has its own vertices, edges and faces, so cubes that touch
+    // share nothing and facets are the cubes in that order.
+    pub fn unfold_tesseract(center: Point4, size: f64, t: f64) -> Shape4 {
+        let mut shape = Shape4 {
+            vertices: Vec::with_capacity(8 * 8),
+            edges: Vec::with_capacity(8 * 12),
+            edge_styles: Vec::new(),
+            faces: Vec::with_capacity(8 * 12),
+            facets: Vec::with_capacity(8),
+            center: None,
+        };
+        Self::unfold_tesseract_into(&mut shape, center, size, t);
+        shape
+    }
+
+    // Same as unfold_tesseract but refills an existing shape, so unfolding
+    // every frame doesn't allocate once its buffers are big enough.
+    pub fn unfold_tesseract_into(shape: &mut Shape4, center: Point4, size: f64, t: f64) {
+        let angle = t.clamp(0.0, 1.0) * FRAC_PI_2;
+        // (fixed axis and its value, folds applied from the cube outwards)
+        let mut cubes = [((3, 0.0), [None::<Fold>; 2]); 8];
+        for axis in 0..3 {
+            for (k, side) in [0.0, 1.0].into_iter().enumerate() {
+                let fold = Fold {
+                    axis,
+                    hinge: (side, 0.0),
+                    direction: if side > 0.0 { 1.0 } else { -1.0 },
+                };
+                cubes[1 + 2 * axis + k] = ((axis, side), [Some(fold), None]);
+            }
+        }
+        // the opposite cube turns onto the end of the low z one first
+        let low_z = cubes[5].1[0];
+        let onto_low_z = Fold {
+            axis: 2,
+            hinge: (0.0, 1.0),
+            direction: -1.0,
+        };
+        cubes[7] = ((3, 1.0), [Some(onto_low_z), low_z]);
+
+        shape.vertices.clear();
+        shape.edges.clear();
+        shape.edge_styles.clear();
+        shape.faces.clear();
+        shape.facets.truncate(cubes.len());
+        shape.center = Some(center);
+        for (cube, ((fixed, value), folds)) in cubes.into_iter().enumerate() {
+            let mut free = [0; 3];
+            for (k, axis) in (0..4).filter(|a| *a != fixed).enumerate() {
+                free[k] = axis;
+            }
+            let first = shape.vertices.len();
+            // corners by which of the free axes are at 1
+            for mask in 0..8 {
+                let mut p = [0.0; 4];
+                p[fixed] = value;
+                for (bit, axis) in free.iter().enumerate() {
+                    p[*axis] = (mask >> bit & 1) as f64;
+                }
+                let p = folds.iter().flatten().fold(p, |p, fold| fold.apply(p, angle));
+                let offset = Point4(p[0] - 0.5, p[1] - 0.5, p[2] - 0.5, p[3] - 0.5) * size;
+                shape.vertices.push(center + offset);
+            }
+            for mask in 0..8 {
+                for bit in [1, 2, 4] {
+                    if mask & bit == 0 {
+                        shape.edges.push(Edge::new(first + mask, first + (mask | bit)));
+                    }
+                }
+            }
+            let faces = shape.faces.len();
+            for (i, j) in [(1, 2), (1, 4), (2, 4)] {
+                for base in [0, 7 & !(i | j)] {
+                    let [p, q, r, s] = [base, base | i, base | i | j, base | j].map(|m| first + m);
+                    shape.faces.push(Face(p, q, r));
+                    shape.faces.push(Face(p, r, s));
+                }
+            }
+            // the facets already there keep their buffers too
+            if cube == shape.facets.len() {
+                shape.facets.push(Facet {
+                    vertices: Vec::with_capacity(8),
+                    faces: Vec::with_capacity(12),
+                });
+            }
+            let facet = &mut shape.facets[cube];
+            facet.vertices.clear();
+            facet.vertices.extend(first..first + 8);
+            facet.faces.clear();
+            facet.faces.extend(faces..shape.faces.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::{Point4, Shape4};
+
+    #[test]
+    fn dali_cross() {
+        let folded = Shape4::unfold_tesseract(Point4::zero(), 2.0, 0.0);
+        assert_eq!(folded.vertices.len(), 64);
+        assert_eq!((folded.edges.len(), folded.faces.len(), folded.facets.len()), (96, 96, 8));
+        // all on the corners of the tesseract
+        for v in &folded.vertices {
+            assert!([v.0, v.1, v.2, v.3].iter().all(|c| (c.abs() - 1.0).abs() < 1e-9));
+        }
+
+        let net = Shape4::unfold_tesseract(Point4::zero(), 2.0, 1.0);
+        // flat in w, four cubes high along z and three wide along x and y
+        let range = |c: fn(&Point4) -> f64| {
+            net.vertices.iter().map(c).fold((f64::MAX, f64::MIN), |(lo, hi), c| (lo.min(c), hi.max(c)))
+        };
+        let rounded = |(lo, hi): (f64, f64)| (lo.round(), hi.round());
+        assert_eq!(rounded(range(|v| v.3)), (-1.0, -1.0));
+        assert_eq!(rounded(range(|v| v.0)), (-3.0, 3.0));
+        assert_eq!(rounded(range(|v| v.1)), (-3.0, 3.0));
+        assert_eq!(rounded(range(|v| v.2)), (-5.0, 3.0));
+        // the opposite cube ends up at the bottom of the column
+        let opposite = &net.facets[7];
+        assert!(opposite.vertices.iter().all(|v| net.vertices[*v].2 < -2.9));
+
+        // refilled in place, the same as a new one
+        let mut refilled = Shape4::unfold_tesseract(Point4::zero(), 2.0, 0.3);
+        Shape4::unfold_tesseract_into(&mut refilled, Point4::zero(), 2.0, 1.0);
+        assert_eq!(refilled.vertices.len(), 64);
+        assert!(refilled.vertices.iter().zip(&net.vertices).all(|(a, b)| (*a - *b).magnitude() < 1e-9));
+        assert!(refilled.facets.iter().zip(&net.facets).all(|(a, b)| a.vertices == b.vertices && a.faces == b.faces));
+    }
+}
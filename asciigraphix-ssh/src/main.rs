@@ -14,6 +14,7 @@ use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 
 type SshTerminal = Terminal<CrosstermBackend<TerminalHandle>>;
 
+use asciigraphix_core::color::ColorDepth;
 use asciigraphix_tui::App;
 
 struct TerminalHandle {
@@ -48,12 +49,8 @@ impl std::io::Write for TerminalHandle {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        let result = self.sender.send(self.sink.clone());
-        if result.is_err() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::BrokenPipe,
-                result.unwrap_err(),
-            ));
+        if let Err(e) = self.sender.send(self.sink.clone()) {
+            return Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, e));
         }
 
         self.sink.clear();
@@ -65,6 +62,9 @@ impl std::io::Write for TerminalHandle {
 struct AppServer {
     clients: Arc<Mutex<HashMap<usize, (SshTerminal, App)>>>,
     id: usize,
+    // $TERM and $COLORTERM of the client, used to pick the color depth
+    term: Option<String>,
+    colorterm: Option<String>,
 }
 
 impl AppServer {
@@ -72,6 +72,8 @@ impl AppServer {
         Self {
             clients: Arc::new(Mutex::new(HashMap::new())),
             id: 0,
+            term: None,
+            colorterm: None,
         }
     }
 
@@ -119,6 +121,14 @@ impl AppServer {
         });
         app.handle_event(event);
     }
+
+    async fn update_color_depth(&mut self) {
+        let depth = ColorDepth::from_env(self.colorterm.as_deref(), self.term.as_deref());
+        let mut clients = self.clients.lock().await;
+        if let Some((_, app)) = clients.get_mut(&self.id) {
+            app.set_color_depth(depth);
+        }
+    }
 }
 
 impl Server for AppServer {
@@ -234,7 +244,7 @@ impl Handler for AppServer {
     async fn pty_request(
         &mut self,
         channel: ChannelId,
-        _: &str,
+        term: &str,
         col_width: u32,
         row_height: u32,
         _: u32,
@@ -249,14 +259,40 @@ impl Handler for AppServer {
             height: row_height as u16,
         };
 
-        let mut clients = self.clients.lock().await;
-        let (terminal, _) = clients.get_mut(&self.id).unwrap();
-        terminal.resize(rect)?;
+        {
+            let mut clients = self.clients.lock().await;
+            let (terminal, _) = clients.get_mut(&self.id).unwrap();
+            terminal.resize(rect)?;
+        }
+
+        self.term = Some(term.to_string());
+        self.update_color_depth().await;
 
         session.channel_success(channel)?;
 
         Ok(())
     }
+
+    /// The client sets an environment variable. Only `COLORTERM` is of
+    /// interest, most clients don't send it unless configured to.
+    async fn env_request(
+        &mut self,
+        channel: ChannelId,
+        variable_name: &str,
+        variable_value: &str,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if variable_name == "COLORTERM" {
+            self.colorterm = Some(variable_value.to_string());
+            if self.term.is_some() {
+                self.update_color_depth().await;
+            }
+            session.channel_success(channel)?;
+        } else {
+            session.channel_failure(channel)?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for AppServer {
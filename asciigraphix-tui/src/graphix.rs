@@ -1,21 +1,141 @@
-use asciigraphix_core::{shapes::{Point, Shape}, Display};
-use ratatui::{style::{Color, Style, Stylize}, widgets::Widget};
+use asciigraphix_core::{color::{ColorDepth, Dither, Quantizer}, cue::{DepthCue, WGradient}, labels::DebugOverlay, plot::{Label, Plot}, shapes::{Point, Shape, Shape4}, Cell, Display, FragmentKind, GlyphStyle, RGB};
+use ratatui::{style::{Style, Stylize}, widgets::StatefulWidget};
 
-use crate::utils::ColorWrapper;
+use crate::utils::{self, ColorWrapper};
 
-pub struct Graphix<'a> {
-    shape: &'a Shape,
+pub struct Graphix {
     cam_pos: Point,
     cam_direction: Point,
     accent_color: ColorWrapper,
     bg_color: ColorWrapper
+}
+
+// Everything that is reused between frames: the display with its buffers and
+// the shape that is drawn.
+pub struct GraphixState {
+    display: Display,
+    shape: Shape,
+    cells: Vec<Cell>,
+    quantizer: Quantizer,
+    // `None` draws every point as ∷
+    glyph_style: Option<GlyphStyle>,
+    debug_overlay: Option<DebugOverlay>,
+    // colors by w instead of the accent color
+    w_colors: bool,
+    // facet of the 4D shape picked out, and how many there are
+    picked_facet: Option<usize>,
+    facets: usize,
+    // the values along the axes when showing a plot, which is colored by
+    // height
+    labels: Vec<Label>,
+    plotting: bool,
+}
+
+impl Default for GraphixState {
+    fn default() -> Self {
+        let mut display = Display::new(0, 0, Point::zero(), Point::e(2), 100.0);
+        // half as bright from 30 to 100 away, and bold when nearer than 60
+        display.set_depth_cue(DepthCue {
+            near: 30.0,
+            far: 100.0,
+            max_fog: 0.5,
+            bold_within: Some(60.0),
+            ..DepthCue::default()
+        });
+        Self {
+            display,
+            shape: Shape {
+                vertices: Vec::new(),
+                edges: Vec::new(),
+                faces: Vec::new(),
+                w: Vec::new(),
+                colors: Vec::new(),
+                edge_styles: Vec::new(),
+                center: None,
+            },
+            cells: Vec::new(),
+            quantizer: Quantizer::new(ColorDepth::TrueColor, Dither::None),
+            glyph_style: None,
+            debug_overlay: None,
+            w_colors: false,
+            picked_facet: None,
+            facets: 0,
+            labels: Vec::new(),
+            plotting: false,
+        }
+    }
+}
+
+impl GraphixState {
+    pub fn project(&mut self, shape: &Shape4) {
+        shape.project_to_3d_into(&mut self.shape);
+        self.facets = shape.facets.len();
+        match self.picked_facet {
+            Some(facet) => shape.highlight_facet(&mut self.shape, facet),
+            None => self.shape.faces.clear(),
+        }
+        self.labels.clear();
+        self.plotting = false;
+    }
+
+    pub fn plot(&mut self, plot: &Plot) {
+        self.shape.clear();
+        self.shape.append(&plot.shape);
+        self.labels.clone_from(&plot.labels);
+        self.plotting = true;
+    }
+
+    // Off, then each facet of the shape in turn, shown as translucent faces.
+    pub fn cycle_picked_facet(&mut self) {
+        self.picked_facet = match self.picked_facet {
+            None if self.facets > 0 => Some(0),
+            Some(facet) if facet + 1 < self.facets => Some(facet + 1),
+            _ => None,
+        };
+        self.display.set_translucent(self.picked_facet.is_some());
+    }
+
+    pub fn set_depth_cue(&mut self, cue: DepthCue) {
+        self.display.set_depth_cue(cue);
+    }
+
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.quantizer = Quantizer::new(depth, Dither::Ordered);
+    }
 
+    // Switches between ∷ and line drawing glyphs that follow the edges.
+    pub fn toggle_line_glyphs(&mut self) {
+        self.glyph_style = match self.glyph_style {
+            None => Some(GlyphStyle::Slope),
+            Some(_) => None,
+        };
+        if let Some(style) = self.glyph_style {
+            self.display.set_glyph_style(style);
+        }
+    }
+
+    pub fn toggle_w_colors(&mut self) {
+        self.w_colors = !self.w_colors;
+        self.display.set_w_gradient(self.w_colors.then(WGradient::default));
+    }
+
+    // Off, vertex indices, vertex and edge indices.
+    pub fn cycle_debug_overlay(&mut self) {
+        self.debug_overlay = match self.debug_overlay {
+            None => Some(DebugOverlay::default()),
+            Some(DebugOverlay { edges: false, .. }) => Some(DebugOverlay {
+                edges: true,
+                ..DebugOverlay::default()
+            }),
+            Some(_) => None,
+        };
+        self.display.set_debug_overlay(self.debug_overlay);
+    }
 }
 
-impl<'a> Graphix<'a> {
-    pub fn new(shape: &'a Shape, cam_pos: Point, cam_direction: Point, accent_color: ColorWrapper, bg_color: ColorWrapper) -> Self {
+impl Graphix {
+    pub fn new(cam_pos: Point, cam_direction: Point, accent_color: ColorWrapper, bg_color: ColorWrapper) -> Self {
         Self {
-            shape,
             cam_pos,
             cam_direction,
             accent_color,
@@ -24,41 +144,74 @@ impl<'a> Graphix<'a> {
     }
 }
 
-impl<'a> Widget for &Graphix<'a> {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+impl StatefulWidget for &Graphix {
+    type State = GraphixState;
+
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer, state: &mut GraphixState)
     where
         Self: Sized,
     {
-        let mut display = Display::new(
-            area.width as usize,
-            area.height as usize,
-            self.cam_pos,
-            self.cam_direction,
-            100.0,
-        );
-
-        for (i, (depth, _)) in display.render(&self.shape).iter().enumerate() {
+        let display = &mut state.display;
+        display.resize(area.width as usize, area.height as usize);
+        display.set_camera(self.cam_pos, self.cam_direction);
+
+        state.cells.clear();
+        let cue = *display.depth_cue();
+        let accent: RGB = self.accent_color.into();
+        display.render(&state.shape);
+        display.draw_labels(&state.labels, accent);
+        for (i, (depth, _)) in display.frame().iter().enumerate() {
+            let depth = *depth as f64;
+            let color = if depth == 0.0 {
+                RGB(0, 0, 0)
+            } else if state.w_colors || state.plotting {
+                // already faded
+                display.cells()[i].fg.unwrap_or(accent)
+            } else {
+                cue.apply(accent, depth)
+            };
+            state.cells.push(Cell {
+                glyph: '∷',
+                fg: Some(color),
+                bg: Some(self.bg_color.into()),
+                bold: cue.bold(depth),
+            });
+        }
+        for ((cell, shaded), fragment) in state.cells.iter_mut().zip(display.cells()).zip(display.fragments()) {
+            let face = fragment.is_some_and(|f| f.kind == FragmentKind::Face);
+            if state.glyph_style.is_some() || face {
+                cell.glyph = shaded.glyph;
+            }
+            // thick edges
+            cell.bold |= shaded.bold;
+            if face && let Some(RGB(r, g, b)) = cell.fg {
+                cell.fg = Some(RGB(r / 2, g / 2, b / 2));
+            }
+        }
+        for ((cell, shaded), overlaid) in state.cells.iter_mut().zip(display.cells()).zip(display.overlay()) {
+            if *overlaid {
+                *cell = Cell {
+                    bg: cell.bg,
+                    ..*shaded
+                };
+            }
+        }
+        // keeps the depth gradient visible on terminals with few colors
+        state.quantizer.quantize(&mut state.cells, area.width as usize);
+
+        for (i, cell) in state.cells.iter().enumerate() {
             let x = i % area.width as usize;
             let y = (i - x) / area.width as usize;
 
-            let str = "∷";
-
-            // linear interpolation from 0.5 - 1.0 as depth 30.0 - 100.0
-            let coef = (-0.5/70.0 * (depth - 30.0) + 1.0).min(1.0).max(0.5);
-            let r = coef * self.accent_color.r as f32;
-            let g = coef * self.accent_color.g as f32;
-            let b = coef * self.accent_color.b as f32;
-            let mut color = Color::Rgb(r as u8, g as u8, b as u8);
-            if *depth == 0.0 {
-                color = Color::Rgb(0,0,0);
-            }
             let mut style = Style::new()
-                .fg(color)
-                .bg(self.bg_color.into());
-            if *depth < 60.0 {
+                .fg(utils::terminal_color(cell.fg, state.quantizer.depth()))
+                .bg(utils::terminal_color(cell.bg, state.quantizer.depth()));
+            if cell.bold {
                 style = style.bold();
             }
-            buf.set_string(area.x + x as u16, area.y + y as u16, str, style);
+            buf[(area.x + x as u16, area.y + y as u16)]
+                .set_char(cell.glyph)
+                .set_style(style);
         }
     }
 }
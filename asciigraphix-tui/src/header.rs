@@ -12,8 +12,8 @@ pub struct Header {
 impl Header {
     pub fn new(text: String, style: Style, cursor: Style) -> Self {
         let text_rendered = Self::render(text);
-        let height = (&text_rendered).lines().count();
-        let width = (&text_rendered).lines().next().unwrap_or("").len();
+        let height = text_rendered.lines().count();
+        let width = text_rendered.lines().next().unwrap_or("").len();
 
         Self {
             text: text_rendered,
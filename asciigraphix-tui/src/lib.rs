@@ -1,23 +1,32 @@
 use std::{
     io,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
-use asciigraphix_core::shapes::{Point, Point4, Shape, Shape4};
+use asciigraphix_core::{
+    color::ColorDepth,
+    plot::{Plot, PlotOptions},
+    scene::Transform,
+    shapes::{Point, Point4, Shape, Shape4},
+    Cell,
+};
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{
-    layout::{Alignment, Margin, Rect}, prelude::CrosstermBackend, style::{Color, Style}, widgets::{Clear, Block, Borders, Gauge, Paragraph, Widget}, Frame, Terminal
+    layout::{Alignment, Margin, Rect}, prelude::CrosstermBackend, style::{Color, Style}, widgets::{Clear, Block, Borders, Gauge, Paragraph, StatefulWidget, Widget}, Frame, Terminal
 };
 
-use crate::{graphix::Graphix, header::Header, utils::ColorWrapper};
+use crate::{graphix::{Graphix, GraphixState}, header::Header, recording::Recording, utils::ColorWrapper};
 
 mod graphix;
 mod header;
+mod recording;
 mod utils;
 
 pub struct App {
     shape: Shape,
     shape4: Shape4,
+    graphix: GraphixState,
     cam_pos: Point,
     cam_direction: Point,
     rotations3d: (f64, f64, f64),
@@ -33,23 +42,32 @@ pub struct App {
     exit: bool,
     help: bool,
     explore: bool,
+    // time unfolding started and the turn of the net
+    unfolding: Option<(Duration, (f64, f64, f64))>,
+    // a function plot shown instead of the tesseract
+    plot: Option<Plot>,
     primary_color: ColorWrapper,
     fg_color: ColorWrapper,
-    bg_color: ColorWrapper
+    bg_color: ColorWrapper,
+    color_depth: ColorDepth,
+    // recording is only offered when there is somewhere to put the files
+    record_dir: Option<PathBuf>,
+    recording: Option<Recording>,
+    toggle_recording: bool,
+    export_html: bool,
+    // the screen to export, captured while drawing and saved in `update`
+    html_capture: Option<(Vec<Cell>, usize)>,
+    // what went wrong with the last recording or export, shown until the
+    // next one
+    record_status: Option<String>,
 }
 
 impl Default for App {
     fn default() -> Self {
-        const L: f64 = 30.0;
         App {
             shape: Shape::generate_cube(Point::zero(), 1.0),
-            shape4: Shape4::generate_4d_paralellepiped(
-                Point4::zero() - Point4(1.0, 1.0, 0.7, 0.7) * L / 2.0,
-                Point4::e(1) * L,
-                Point4::e(2) * L,
-                Point4::e(3) * 0.7 * L,
-                Point4::e(4) * 0.7 * L,
-            ),
+            shape4: tesseract(),
+            graphix: GraphixState::default(),
             cam_pos: Point(0.0, -80.0, 0.0),
             cam_direction: Point(0.0, 1.0, 0.0),
             rotations3d: (0.01, 0.0, 0.01),
@@ -65,15 +83,63 @@ impl Default for App {
             exit: false,
             help: false,
             explore: false,
+            unfolding: None,
+            plot: None,
             primary_color: ColorWrapper::rgb(192, 80, 80),
             fg_color: ColorWrapper::rgb(240, 240, 240),
-            bg_color: ColorWrapper::rgb(30, 30, 30)
+            bg_color: ColorWrapper::rgb(30, 30, 30),
+            color_depth: ColorDepth::TrueColor,
+            record_dir: None,
+            recording: None,
+            toggle_recording: false,
+            export_html: false,
+            html_capture: None,
+            record_status: None,
         }
     }
 }
 
 const HEADER: &str = "TeSSHeract";
 
+fn tesseract() -> Shape4 {
+    const L: f64 = 30.0;
+    Shape4::generate_4d_paralellepiped(
+        Point4::zero() - Point4(1.0, 1.0, 0.7, 0.7) * L / 2.0,
+        Point4::e(1) * L,
+        Point4::e(2) * L,
+        Point4::e(3) * 0.7 * L,
+        Point4::e(4) * 0.7 * L,
+    )
+}
+
+// How far plots are tipped towards the camera, to see them from above.
+const PLOT_TILT: f64 = 0.5;
+
+// The sombrero z = sin(r) / r, tipped towards the camera.
+fn sombrero() -> Plot {
+    let options = PlotOptions {
+        size: 40.0,
+        height: 20.0,
+        ..PlotOptions::default()
+    };
+    let f = |x: f64, y: f64| {
+        let r = (x * x + y * y).sqrt();
+        if r == 0.0 { 1.0 } else { r.sin() / r }
+    };
+    let mut plot = Plot::surface(f, -10.0..=10.0, -10.0..=10.0, &options);
+    plot.transform(&Transform::rotation((PLOT_TILT, 0.0, 0.0)));
+    plot
+}
+
+// How far the tesseract is unfolded, going back and forth and resting a
+// little when folded and when flat.
+fn unfolding(time: Duration) -> f64 {
+    const PERIOD: f64 = 8.0;
+    let phase = (time.as_secs_f64() / PERIOD).fract();
+    let t = ((1.0 - (2.0 * phase - 1.0).abs()) * 1.4 - 0.2).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
 impl App {
     pub fn run<T: std::io::Write>(
         &mut self,
@@ -84,8 +150,22 @@ impl App {
             self.update()?;
             terminal.draw(|frame| self.draw(frame))?;
         }
-        Ok(())
+        match self.recording.take() {
+            Some(recording) => recording.finish(),
+            None => Ok(()),
+        }
+    }
+    // What the terminal can show, colors are reduced to fit.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+        self.graphix.set_color_depth(depth);
     }
+
+    // Lets c record the app into `dir`, as an asciicast and a GIF.
+    pub fn enable_recording(&mut self, dir: PathBuf) {
+        self.record_dir = Some(dir);
+    }
+
     pub fn handle_event(&mut self, event: Event) {
         const ROTATION_AMOUNT: f64 = 0.01;
         const CAM_ROTATION: (f64, f64) = (0.04, 0.04);
@@ -111,16 +191,45 @@ impl App {
                 KeyCode::Char('Q') => {
                     self.explore = !self.explore;
                 }
-                d if !self.explore => match d {
-                    KeyCode::Char('w') => {
-                        if self.confusion <= 80 {
-                            self.confusion += 20
+                KeyCode::Char('v') => {
+                    self.graphix.toggle_line_glyphs();
+                }
+                KeyCode::Char('i') => {
+                    self.graphix.cycle_debug_overlay();
+                }
+                KeyCode::Char('g') => {
+                    self.graphix.toggle_w_colors();
+                }
+                KeyCode::Char('p') => {
+                    self.graphix.cycle_picked_facet();
+                }
+                KeyCode::Char('u') => {
+                    self.unfolding = match self.unfolding {
+                        None => Some((self.elapsed, (0.0, 0.0, 0.0))),
+                        Some(_) => {
+                            self.shape4 = tesseract();
+                            None
                         }
+                    };
+                }
+                KeyCode::Char('f') => {
+                    self.plot = match self.plot {
+                        None => Some(sombrero()),
+                        Some(_) => None,
+                    };
+                }
+                KeyCode::Char('c') if self.record_dir.is_some() => {
+                    self.toggle_recording = true;
+                }
+                KeyCode::Char('e') if self.record_dir.is_some() => {
+                    self.export_html = true;
+                }
+                d if !self.explore => match d {
+                    KeyCode::Char('w') if self.confusion <= 80 => {
+                        self.confusion += 20
                     }
-                    KeyCode::Char('s') => {
-                        if self.confusion >= 20 {
-                            self.confusion -= 20
-                        }
+                    KeyCode::Char('s') if self.confusion >= 20 => {
+                        self.confusion -= 20
                     }
                     _ => {}
                 },
@@ -202,7 +311,46 @@ impl App {
         Ok(())
     }
 
+    // Drops the recording, the app goes on without it.
+    fn recording_failed(&mut self, error: io::Error) {
+        self.recording = None;
+        self.record_status = Some(format!(" recording failed: {} ", error));
+    }
+
     pub fn update(&mut self) -> io::Result<()> {
+        if self.toggle_recording {
+            self.toggle_recording = false;
+            self.record_status = None;
+            match (self.recording.take(), &self.record_dir) {
+                (Some(recording), _) => {
+                    if let Err(error) = recording.finish() {
+                        self.recording_failed(error);
+                    }
+                }
+                (None, Some(dir)) => {
+                    match crossterm::terminal::size()
+                        .and_then(|(columns, rows)| Recording::start(dir, columns as usize, rows as usize))
+                    {
+                        Ok(recording) => self.recording = Some(recording),
+                        Err(error) => self.recording_failed(error),
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        if let Some(recording) = &mut self.recording
+            && let Err(error) = recording.write()
+        {
+            self.recording_failed(error);
+        }
+        if let Some((cells, columns)) = self.html_capture.take()
+            && let Some(dir) = &self.record_dir
+        {
+            self.record_status = recording::export_html(dir, &cells, columns)
+                .err()
+                .map(|error| format!(" export failed: {} ", error));
+        }
+
         if self.reset {
             self.reset = false;
 
@@ -214,14 +362,11 @@ impl App {
             self.cam_direction = Point(0.0, 1.0, 0.0);
             self.cam_pos = Point(0.0, -80.0, 0.0);
 
-            const L: f64 = 30.0;
-            self.shape4 = Shape4::generate_4d_paralellepiped(
-                Point4::zero() - Point4(1.0, 1.0, 0.7, 0.7) * L / 2.0,
-                Point4::e(1) * L,
-                Point4::e(2) * L,
-                Point4::e(3) * 0.7 * L,
-                Point4::e(4) * 0.7 * L,
-            );
+            self.unfolding = None;
+            self.shape4 = tesseract();
+            if self.plot.is_some() {
+                self.plot = Some(sombrero());
+            }
         }
 
         if self.paused {
@@ -240,7 +385,7 @@ impl App {
         }
 
         self.shape.rotate(
-            &self.shape.center.unwrap_or(Point::zero()),
+            &self.shape.center.or_else(|| self.shape.centroid()).unwrap_or(Point::zero()),
             self.rotations3d,
         );
 
@@ -260,7 +405,25 @@ impl App {
             }
         }
 
-        self.shape4.rotate(&Point4::zero(), self.rotations4d);
+        match &mut self.unfolding {
+            // a net is only flat in 3D when turned without w
+            Some((start, turn)) => {
+                const SIZE: f64 = 14.0;
+                *turn = (turn.0, turn.1 + 0.004, turn.2 + 0.008);
+                let t = unfolding(self.elapsed.saturating_sub(*start));
+                Shape4::unfold_tesseract_into(&mut self.shape4, Point4(0.0, 0.0, SIZE / 2.0, 0.0), SIZE, t);
+                self.shape4.rotate(&Point4::zero(), (turn.0, turn.1, turn.2, 0.0, 0.0, 0.0));
+            }
+            None => self.shape4.rotate(&Point4::zero(), self.rotations4d),
+        }
+
+        // turning about its own z, like on a turntable
+        if let Some(plot) = &mut self.plot {
+            let turn = Transform::rotation((-PLOT_TILT, 0.0, 0.0))
+                .then(&Transform::rotation((0.0, 0.0, 0.005)))
+                .then(&Transform::rotation((PLOT_TILT, 0.0, 0.0)));
+            plot.transform(&turn);
+        }
 
         if self.header_text.len() >= String::from(HEADER).len() {
             self.header_cursor_blink_state =
@@ -270,25 +433,28 @@ impl App {
         Ok(())
     }
 
-    pub fn draw(&self, frame: &mut Frame) {
+    pub fn draw(&mut self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
 }
 
-impl Widget for &App {
+impl Widget for &mut App {
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
         // actual tesseract render
+        match &self.plot {
+            Some(plot) => self.graphix.plot(plot),
+            None => self.graphix.project(&self.shape4),
+        }
         Graphix::new(
-            &self.shape4.project_to_3d(),
             self.cam_pos,
             self.cam_direction,
             self.primary_color.lighten(1.5),
             self.bg_color
         )
-        .render(area, buf);
+        .render(area, buf, &mut self.graphix);
 
         if !self.explore {
             // header
@@ -302,8 +468,8 @@ impl Widget for &App {
 
             // confusion meter
             let h = Header::new(self.header_text.clone(), header_style, cursor_style);
-            let h_height = (&h.height).clone() as u16;
-            let h_width = (&h.width).clone() as u16;
+            let h_height = h.height as u16;
+            let h_width = h.width as u16;
             h.render(
                 Rect::new(area.width / 2 - h_width / 2, 5, h_width, h_height),
                 buf,
@@ -362,6 +528,16 @@ In this mode you can use:
     - arrow keys to look,
     - h,j,k,l,n,m to inrease rotation on a plane of rotation,
     - H,J,K,L,N,M to decrease rotation on a plane of rotation
+
+v switches between dotted and line drawing glyphs,
+i shows vertex and then also edge indices,
+g colors by the 4th coordinate, blue for -w through red for +w,
+p picks out each of the cubes bounding the tesseract in turn,
+u unfolds the tesseract into its net of eight cubes and back,
+f plots z = sin(r) / r with its axes instead of the tesseract.
+
+When running locally, c starts/stops recording an asciicast and a GIF,
+and e saves the current frame as an HTML page.
                 ",
             )
             .block(
@@ -372,5 +548,28 @@ In this mode you can use:
             .style(Style::new().fg(self.fg_color.into()).bg(self.bg_color.into()))
             .render(help_area, buf);
         }
+
+        if self.export_html {
+            self.export_html = false;
+            let mut cells = Vec::new();
+            utils::buffer_cells(buf, &mut cells);
+            self.html_capture = Some((cells, area.width as usize));
+        }
+        // written in `update`, away from drawing
+        if let Some(recording) = &mut self.recording {
+            recording.capture(buf);
+        }
+        let status = match (&self.recording, &self.record_status) {
+            (Some(_), _) => Some(" ● REC "),
+            (None, status) => status.as_deref(),
+        };
+        if let Some(status) = status {
+            let width = (status.chars().count() as u16).min(area.width.saturating_sub(1));
+            Paragraph::new(status)
+                .style(Style::new().fg(self.fg_color.into()).bg(self.primary_color.into()))
+                .render(Rect::new(area.x + 1, area.y, width, 1.min(area.height)), buf);
+        }
+
+        utils::downsample_colors(buf, self.color_depth);
     }
 }
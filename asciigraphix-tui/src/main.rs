@@ -1,11 +1,13 @@
 use std::io;
 
-use ratatui;
+use asciigraphix_core::color::ColorDepth;
 use asciigraphix_tui::App;
 
 fn main() -> io::Result<()> {
-    let mut terminal = ratatui::init();
     let mut app = App::default();
+    app.set_color_depth(ColorDepth::detect());
+    app.enable_recording(std::env::current_dir()?);
+    let mut terminal = ratatui::init();
     let result = app.run(&mut terminal);
     ratatui::restore();
     result
@@ -0,0 +1,76 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use asciigraphix_core::{html, record::Recorder, Cell};
+use ratatui::buffer::Buffer;
+
+use crate::utils;
+
+// Records what the app draws to an asciicast and a GIF at the same time.
+pub struct Recording {
+    recorders: Vec<Recorder>,
+    start: Instant,
+    cells: Vec<Cell>,
+    columns: usize,
+    // when the cells were captured, if they haven't been written yet
+    captured: Option<Duration>,
+}
+
+impl Recording {
+    pub fn start(dir: &Path, columns: usize, rows: usize) -> io::Result<Self> {
+        let recorders = ["cast", "gif"]
+            .iter()
+            .map(|extension| Recorder::create(file_name(dir, extension), columns, rows))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            recorders,
+            start: Instant::now(),
+            cells: Vec::new(),
+            columns: 0,
+            captured: None,
+        })
+    }
+
+    // Copies what is on screen while drawing, `write` encodes it later.
+    pub fn capture(&mut self, buf: &Buffer) {
+        utils::buffer_cells(buf, &mut self.cells);
+        self.columns = buf.area.width as usize;
+        self.captured = Some(self.start.elapsed());
+    }
+
+    // Writes the frame captured last, if it hasn't been already.
+    pub fn write(&mut self) -> io::Result<()> {
+        let Some(at) = self.captured.take() else {
+            return Ok(());
+        };
+        for recorder in &mut self.recorders {
+            recorder.frame(&self.cells, self.columns, at)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        for recorder in self.recorders {
+            recorder.finish()?;
+        }
+        Ok(())
+    }
+}
+
+// Saves cells captured from the screen as an HTML page.
+pub fn export_html(dir: &Path, cells: &[Cell], columns: usize) -> io::Result<()> {
+    let frame = html::spans(cells, columns);
+    std::fs::write(file_name(dir, "html"), html::page(&[(Duration::ZERO, frame)]))
+}
+
+// Files are named after the current time, in `dir`.
+fn file_name(dir: &Path, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    dir.join(format!("asciigraphix-{}.{}", timestamp, extension))
+}
@@ -1,4 +1,11 @@
-use ratatui::style::Color;
+use asciigraphix_core::{
+    color::{self, ColorDepth},
+    Cell, RGB,
+};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
 
 #[derive(Clone, Copy)]
 pub struct ColorWrapper {
@@ -14,7 +21,6 @@ impl ColorWrapper {
 
     /// factor 1.0 changes nothing. factor > 1.0 will brighten.
     pub fn lighten(&self, factor: f32) -> Self {
-        let factor = factor;
         let r = factor * self.r as f32;
         let g = factor * self.g as f32;
         let b = factor * self.b as f32;
@@ -29,3 +35,80 @@ impl From<ColorWrapper> for Color {
         Self::Rgb(value.r, value.g, value.b)
     }
 }
+
+impl From<ColorWrapper> for RGB {
+    fn from(value: ColorWrapper) -> Self {
+        Self(value.r, value.g, value.b)
+    }
+}
+
+// The 16 colors in palette order.
+const NAMED: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+// What to send for a color on a terminal of the given depth. Colors not in the
+// palette are replaced by the closest one.
+pub fn terminal_color(color: Option<RGB>, depth: ColorDepth) -> Color {
+    let Some(color) = color else {
+        return Color::Reset;
+    };
+    match (depth, depth.index(color)) {
+        (ColorDepth::TrueColor, _) => Color::Rgb(color.0, color.1, color.2),
+        (ColorDepth::Ansi256, Some(i)) => Color::Indexed(i),
+        (ColorDepth::Ansi16, Some(i)) => NAMED[i as usize],
+        _ => Color::Reset,
+    }
+}
+
+// Replaces every truecolor color in the buffer with one the terminal can show.
+pub fn downsample_colors(buf: &mut Buffer, depth: ColorDepth) {
+    if depth == ColorDepth::TrueColor {
+        return;
+    }
+    let convert = |color: Color| match color {
+        Color::Rgb(r, g, b) => terminal_color(Some(RGB(r, g, b)), depth),
+        _ if depth == ColorDepth::Mono => Color::Reset,
+        c => c,
+    };
+    for cell in buf.content.iter_mut() {
+        cell.fg = convert(cell.fg);
+        cell.bg = convert(cell.bg);
+    }
+}
+
+// The other way around, what a terminal color looks like.
+pub fn core_color(color: Color) -> Option<RGB> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some(RGB(r, g, b)),
+        Color::Indexed(i) => Some(color::ansi_256(i)),
+        c => NAMED.iter().position(|n| *n == c).map(|i| color::ANSI_16[i]),
+    }
+}
+
+// Copies the buffer into core cells, e.g. for recording it.
+pub fn buffer_cells(buf: &Buffer, cells: &mut Vec<Cell>) {
+    cells.clear();
+    cells.extend(buf.content.iter().map(|cell| Cell {
+        glyph: cell.symbol().chars().next().unwrap_or(' '),
+        fg: core_color(cell.fg),
+        bg: core_color(cell.bg),
+        bold: cell.modifier.contains(Modifier::BOLD),
+    }));
+}